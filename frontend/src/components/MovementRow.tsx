@@ -1,9 +1,36 @@
 import { Movement } from "../types/type";
-import { ArrowDownCircle, ArrowUpCircle, RefreshCw } from "lucide-react";
+import {
+  ArrowDownCircle,
+  ArrowUpCircle,
+  RefreshCw,
+  ShoppingBag,
+  Undo2,
+  ShieldAlert,
+  Ban,
+  Factory,
+  Trash2,
+} from "lucide-react";
 
-export default function MovementRow({ m }: { m: Movement }) {
+/** A movement can be voided here unless it came from an order, batch or stocktake */
+const canVoid = (m: Movement) =>
+  m.voided_at === null &&
+  m.reversal_of === null &&
+  m.batch_id === null &&
+  m.purchase_order_id === null &&
+  m.sales_order_id === null &&
+  m.stocktake_session_id === null;
+
+export default function MovementRow({
+  m,
+  onVoid,
+}: {
+  m: Movement;
+  onVoid?: (m: Movement) => void;
+}) {
   return (
-    <tr className="hover:bg-stone-50 transition-colors">
+    <tr
+      className={`hover:bg-stone-50 transition-colors ${m.voided_at ? "opacity-50" : ""}`}
+    >
       <td className="px-6 py-4 text-xs text-soap-accent font-mono">
         {new Date(m.created_at).toLocaleString("zh-TW", {
           hour12: false,
@@ -25,6 +52,31 @@ export default function MovementRow({ m }: { m: Movement }) {
             <RefreshCw size={12} /> 校準
           </span>
         )}
+        {m.action_type === "sale" && (
+          <span className="flex items-center gap-1 text-red-600 bg-red-50 px-2 py-0.5 rounded-md w-fit text-xs border border-red-100">
+            <ShoppingBag size={12} /> 銷售
+          </span>
+        )}
+        {m.action_type === "production" && (
+          <span className="flex items-center gap-1 text-green-600 bg-green-50 px-2 py-0.5 rounded-md w-fit text-xs border border-green-100">
+            <Factory size={12} /> 生產
+          </span>
+        )}
+        {m.action_type === "waste" && (
+          <span className="flex items-center gap-1 text-red-600 bg-red-50 px-2 py-0.5 rounded-md w-fit text-xs border border-red-100">
+            <Trash2 size={12} /> 報廢
+          </span>
+        )}
+        {m.action_type === "return" && (
+          <span className="flex items-center gap-1 text-green-600 bg-green-50 px-2 py-0.5 rounded-md w-fit text-xs border border-green-100">
+            <Undo2 size={12} /> 退貨
+          </span>
+        )}
+        {m.action_type === "quarantine" && (
+          <span className="flex items-center gap-1 text-orange-600 bg-orange-50 px-2 py-0.5 rounded-md w-fit text-xs border border-orange-100">
+            <ShieldAlert size={12} /> 隔離
+          </span>
+        )}
       </td>
       <td className="px-6 py-4 text-soap-stone">
         <span className="text-[10px] bg-stone-100 text-stone-400 px-1 rounded mr-2 uppercase">
@@ -33,21 +85,61 @@ export default function MovementRow({ m }: { m: Movement }) {
         {m.item_name}
       </td>
       <td
-        className={`px-6 py-4 text-right font-mono font-bold ${m.action_type === "out" ? "text-red-500" : m.action_type === "adj" ? "text-soap-wood" : "text-green-600"}`}
+        className={`px-6 py-4 text-right font-mono font-bold ${m.voided_at ? "line-through " : ""}${m.action_type === "out" || m.action_type === "sale" || m.action_type === "waste" || m.action_type === "quarantine" ? "text-red-500" : m.action_type === "adj" ? "text-soap-wood" : "text-green-600"}`}
       >
-        {m.action_type === "in" && <>+{m.change_amount}</>}
-        {m.action_type === "out" && <>-{m.change_amount}</>}
-        {m.action_type === "adj" && <>={m.change_amount}</>}
+        {(m.action_type === "in" ||
+          m.action_type === "production" ||
+          m.action_type === "return") && <>+{m.change_amount}</>}
+        {(m.action_type === "out" ||
+          m.action_type === "sale" ||
+          m.action_type === "waste" ||
+          m.action_type === "quarantine") && (
+          <>-{m.change_amount}</>
+        )}
+        {m.action_type === "adj" && (
+          <>
+            {m.change_amount >= 0 ? "+" : ""}
+            {m.change_amount}
+          </>
+        )}
         <span className="text-s font-normal text-soap-accent ml-0.5">
           {m.item_unit}
         </span>
+        {m.action_type === "adj" && m.target_stock !== null && (
+          <div className="text-[10px] font-normal text-soap-accent">
+            目標 {m.target_stock}
+          </div>
+        )}
       </td>
       <td className="px-6 py-4 text-sm text-soap-wood italic font-sans uppercase">
         {m.related_batch ?? "--"}
       </td>
       <td className="px-6 py-4 text-sm text-soap-accent truncate max-w-[200px]">
+        {m.voided_at && (
+          <span className="text-[10px] bg-red-50 text-red-500 border border-red-100 px-1 rounded mr-2">
+            已作廢
+          </span>
+        )}
+        {m.void_of !== null && (
+          <span className="text-[10px] bg-stone-100 text-stone-500 px-1 rounded mr-2">
+            沖銷 #{m.void_of}
+          </span>
+        )}
         {m.note ?? "--"}
       </td>
+      {onVoid && (
+        <td className="px-6 py-4 text-center">
+          {canVoid(m) && (
+            <button
+              onClick={() => onVoid(m)}
+              className="p-2 hover:bg-soap-beige rounded-full text-soap-accent transition-colors"
+              title="作廢"
+            >
+              <Ban size={16} />
+            </button>
+          )}
+        </td>
+      )}
     </tr>
   );
 }
@@ -9,9 +9,10 @@ import {
   ArrowUpRight,
   ChevronRight,
   Beaker,
+  ScanLine,
 } from "lucide-react";
 
-import { Movement, Material, Product } from "../types/type";
+import { CodeMatch, Movement, Material, Product } from "../types/type";
 import MovementRow from "../components/MovementRow";
 
 export default function Dashboard() {
@@ -20,6 +21,9 @@ export default function Dashboard() {
   const [materials, setMaterials] = useState<Material[]>([]);
   const [products, setProducts] = useState<Product[]>([]);
   const [recentMovements, setRecentMovements] = useState<Movement[]>([]);
+  const [scanCode, setScanCode] = useState("");
+  const [scanResult, setScanResult] = useState<CodeMatch | null>(null);
+  const [scanMessage, setScanMessage] = useState<string | null>(null);
 
   const navigate = useNavigate();
 
@@ -44,6 +48,25 @@ export default function Dashboard() {
     loadData();
   }, []);
 
+  // USB scanners type the code and press Enter, which submits the form
+  const handleScan = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const code = scanCode.trim();
+    if (!code) return;
+    try {
+      const found = await invoke<CodeMatch | null>("find_item_by_code", {
+        code,
+      });
+      setScanResult(found);
+      setScanMessage(found ? null : `查無條碼 ${code}`);
+    } catch (err) {
+      setScanResult(null);
+      setScanMessage(`查詢失敗: ${err}`);
+    } finally {
+      setScanCode("");
+    }
+  };
+
   const getIndangerMaterials = () => {
     return materials.filter((m) => {
       return m.low_stock_alert !== null && m.current_stock <= m.low_stock_alert;
@@ -70,6 +93,15 @@ export default function Dashboard() {
           </p>
         </div>
         <div className="flex gap-3">
+          <form onSubmit={handleScan} className="flex items-center gap-2">
+            <ScanLine size={16} className="text-soap-accent" />
+            <input
+              value={scanCode}
+              onChange={(e) => setScanCode(e.target.value)}
+              placeholder="掃描或輸入條碼"
+              className="border border-stone-200 rounded-xl px-3 py-2 text-sm"
+            />
+          </form>
           <button
             onClick={() => navigate("/movements")}
             className="flex items-center gap-2 bg-white border border-stone-200 px-5 py-2.5 rounded-xl text-sm font-bold text-soap-stone shadow-sm hover:bg-stone-50 transition-all"
@@ -79,6 +111,43 @@ export default function Dashboard() {
         </div>
       </div>
 
+      {(scanResult || scanMessage) && (
+        <div className="bg-white border border-stone-200 rounded-xl p-4 flex items-center justify-between shadow-sm">
+          {scanResult ? (
+            <>
+              <div>
+                <p className="font-bold text-soap-stone">
+                  {scanResult.name}
+                  <span className="ml-2 text-xs text-stone-400">
+                    {scanResult.item_type === "product" ? "成品" : "原料"}
+                    {scanResult.sku && ` · ${scanResult.sku}`}
+                    {scanResult.supplier_name &&
+                      ` · ${scanResult.supplier_name}`}
+                  </span>
+                </p>
+                <p className="text-sm text-soap-accent">
+                  庫存 {scanResult.current_stock} {scanResult.unit}
+                </p>
+              </div>
+              <button
+                onClick={() =>
+                  navigate(
+                    scanResult.item_type === "product"
+                      ? "/products"
+                      : "/materials",
+                  )
+                }
+                className="text-sm font-bold text-soap-wood flex items-center gap-1"
+              >
+                查看 <ChevronRight size={14} />
+              </button>
+            </>
+          ) : (
+            <p className="text-sm text-red-600">{scanMessage}</p>
+          )}
+        </div>
+      )}
+
       {loading && <p className="text-center py-8">載入中...</p>}
       {error && (
         <div className="bg-red-50 border border-red-200 rounded-lg p-4 flex items-center gap-3">
@@ -17,7 +17,9 @@ import {
   MovementActionType,
   MOVEMENT_ACTION_LIST,
   MATERIAL_UNIT_LIST,
+  MovementResult,
 } from "../types/type";
+import { errorMessage, shortageMessage } from "../types/errors";
 import {
   MATERIAL_FILTERS,
   MaterialFilterType,
@@ -43,7 +45,7 @@ export default function Materials() {
       const data = await invoke<Material[]>("list_materials");
       setMaterials(data);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -69,7 +71,7 @@ export default function Materials() {
         await invoke("remove_material", { material: { id: materialId } });
         await loadMaterials();
       } catch (err) {
-        setError(err as string);
+        setError(errorMessage(err));
       } finally {
         setLoading(false);
       }
@@ -287,7 +289,7 @@ function MovementForm({
     e.preventDefault();
 
     try {
-      await invoke("add_material_inventory", {
+      const result = await invoke<MovementResult>("add_material_inventory", {
         req: {
           item_id: item.id,
           item_type: "material",
@@ -296,9 +298,10 @@ function MovementForm({
           note: item.note,
         },
       });
+      if (result.warning) alert(shortageMessage(result.warning));
       onFinish();
     } catch (err) {
-      alert(`異動失敗: ${err}`);
+      alert(`異動失敗: ${errorMessage(err)}`);
     }
   };
 
@@ -343,7 +346,7 @@ function MovementForm({
 
       <div className="pt-4 space-y-3">
         <button
-          disabled={isInvalid || qty <= 0}
+          disabled={qty <= 0}
           className="w-full bg-soap-stone text-white py-4 rounded-2xl font-bold shadow-lg hover:opacity-90 disabled:opacity-20 disabled:grayscale transition-all text-lg"
         >
           確認並更新庫存
@@ -374,11 +377,17 @@ function CreateEditForm({
           material: {
             name: String(payload.name),
             category: payload.category ? String(payload.category) : null,
+            sku: payload.sku ? String(payload.sku) : null,
             unit: String(payload.unit),
             current_stock: 0,
             low_stock_alert: payload.low_stock_alert
               ? Number(payload.low_stock_alert)
               : null,
+            density: payload.density ? Number(payload.density) : null,
+            inci_name: payload.inci_name ? String(payload.inci_name) : null,
+            saponified_inci_name: payload.saponified_inci_name
+              ? String(payload.saponified_inci_name)
+              : null,
             note: payload.note ? String(payload.note) : null,
           },
         });
@@ -388,10 +397,18 @@ function CreateEditForm({
           req: {
             name: String(payload.name),
             category: payload.category ? String(payload.category) : null,
+            sku: payload.sku ? String(payload.sku) : null,
             unit: String(payload.unit),
             low_stock_alert: payload.low_stock_alert
               ? Number(payload.low_stock_alert)
               : null,
+            sap_naoh: item?.sap_naoh ?? null,
+            sap_koh: item?.sap_koh ?? null,
+            density: payload.density ? Number(payload.density) : null,
+            inci_name: payload.inci_name ? String(payload.inci_name) : null,
+            saponified_inci_name: payload.saponified_inci_name
+              ? String(payload.saponified_inci_name)
+              : null,
             note: payload.note ? String(payload.note) : null,
           },
         });
@@ -399,7 +416,7 @@ function CreateEditForm({
 
       onFinish();
     } catch (err) {
-      alert(`異動失敗: ${err}`);
+      alert(`異動失敗: ${errorMessage(err)}`);
     }
   };
 
@@ -420,6 +437,18 @@ function CreateEditForm({
             autoFocus
           />
         </div>
+        <div className="col-span-2">
+          <label className="block text-sm font-bold text-soap-stone mb-2">
+            SKU
+          </label>
+          <input
+            name="sku"
+            type="text"
+            defaultValue={item?.sku ?? ""}
+            className="w-full border border-stone-200 rounded-lg"
+            placeholder="留空則自動產生"
+          />
+        </div>
         <div>
           <label className="block text-sm font-bold text-soap-stone mb-2">
             類型
@@ -465,6 +494,43 @@ function CreateEditForm({
             className="w-full border border-stone-200 rounded-lg"
           />
         </div>
+        <div className="col-span-2">
+          <label className="block text-sm font-bold text-soap-stone mb-2">
+            密度 (g/ml，以 mL / L 記錄的油品計算鹼量時使用)
+          </label>
+          <input
+            name="density"
+            type="number"
+            step="0.01"
+            min="0"
+            defaultValue={item?.density ?? ""}
+            className="w-full border border-stone-200 rounded-lg"
+          />
+        </div>
+        <div>
+          <label className="block text-sm font-bold text-soap-stone mb-2">
+            INCI 名稱
+          </label>
+          <input
+            name="inci_name"
+            type="text"
+            defaultValue={item?.inci_name ?? ""}
+            className="w-full border border-stone-200 rounded-lg"
+            placeholder="例如：Olea Europaea Fruit Oil"
+          />
+        </div>
+        <div>
+          <label className="block text-sm font-bold text-soap-stone mb-2">
+            皂化後 INCI 名稱
+          </label>
+          <input
+            name="saponified_inci_name"
+            type="text"
+            defaultValue={item?.saponified_inci_name ?? ""}
+            className="w-full border border-stone-200 rounded-lg"
+            placeholder="例如：Sodium Olivate"
+          />
+        </div>
         <div className="col-span-2">
           <label className="block text-sm font-bold text-soap-stone mb-2">
             備註
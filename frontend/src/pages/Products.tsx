@@ -13,10 +13,13 @@ import {
 
 import {
   Product,
+  ProductCost,
   MovementActionType,
   ViewMode,
   PRODUCT_UNIT_LIST,
+  MovementResult,
 } from "../types/type";
+import { errorMessage, shortageMessage } from "../types/errors";
 import {
   PRODUCT_FILTERS,
   ProductFilterType,
@@ -27,6 +30,7 @@ import { FilterButton } from "../components/FilterButton";
 export default function Products() {
   // --- state management ---
   const [products, setProducts] = useState<Product[]>([]);
+  const [costs, setCosts] = useState<Record<number, ProductCost>>({});
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [filter, setFilter] = useState<ProductFilterType>("all");
@@ -41,8 +45,20 @@ export default function Products() {
     try {
       const data = await invoke<Product[]>("list_products");
       setProducts(data);
+
+      // products without a recipe have no cost and are left blank
+      const results = await Promise.allSettled(
+        data.map((p) =>
+          invoke<ProductCost>("get_product_cost", { productId: p.id }),
+        ),
+      );
+      const costMap: Record<number, ProductCost> = {};
+      results.forEach((r) => {
+        if (r.status === "fulfilled") costMap[r.value.product_id] = r.value;
+      });
+      setCosts(costMap);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -68,7 +84,7 @@ export default function Products() {
         await invoke("remove_product", { product: { id: productId } });
         await loadProducts();
       } catch (err) {
-        setError(err as string);
+        setError(errorMessage(err));
       } finally {
         setLoading(false);
       }
@@ -146,6 +162,7 @@ export default function Products() {
                   <th className="px-6 py-4">產品名稱</th>
                   <th className="px-6 py-4">類型</th>
                   <th className="px-6 py-4 text-right">現存量</th>
+                  <th className="px-6 py-4 text-right">單位成本</th>
                   <th className="px-6 py-4">單位</th>
                   <th className="px-6 py-4">狀態</th>
                   <th className="px-6 py-4 text-center">操作</th>
@@ -155,7 +172,7 @@ export default function Products() {
                 {filteredProducts.length === 0 ? (
                   <tr>
                     <td
-                      colSpan={6}
+                      colSpan={7}
                       className="px-6 py-12 text-center text-stone-400"
                     >
                       暫無產品資料
@@ -176,6 +193,21 @@ export default function Products() {
                       <td className="px-6 py-4 text-right font-mono font-bold">
                         {m.current_stock}
                       </td>
+                      <td className="px-6 py-4 text-right font-mono text-sm text-soap-accent">
+                        {costs[m.id] ? (
+                          <span
+                            title={
+                              costs[m.id].margin_percent !== null
+                                ? `毛利 ${costs[m.id].margin_percent!.toFixed(1)}%`
+                                : undefined
+                            }
+                          >
+                            ${costs[m.id].total.toFixed(1)}
+                          </span>
+                        ) : (
+                          "-"
+                        )}
+                      </td>
                       <td className="px-6 py-4 text-sm text-soap-accent">
                         {m.unit}
                       </td>
@@ -291,7 +323,7 @@ function MovementForm({
     e.preventDefault();
 
     try {
-      await invoke("add_product_inventory", {
+      const result = await invoke<MovementResult>("add_product_inventory", {
         req: {
           item_id: item.id,
           item_type: "product",
@@ -300,9 +332,10 @@ function MovementForm({
           note: item.note,
         },
       });
+      if (result.warning) alert(shortageMessage(result.warning));
       onFinish();
     } catch (err) {
-      alert(`異動失敗: ${err}`);
+      alert(`異動失敗: ${errorMessage(err)}`);
     }
   };
 
@@ -347,7 +380,7 @@ function MovementForm({
 
       <div className="pt-4 space-y-3">
         <button
-          disabled={isInvalid || qty <= 0}
+          disabled={qty <= 0}
           className="w-full bg-soap-stone text-white py-4 rounded-2xl font-bold shadow-lg hover:opacity-90 disabled:opacity-20 disabled:grayscale transition-all text-lg"
         >
           確認並更新庫存
@@ -357,6 +390,9 @@ function MovementForm({
   );
 }
 
+const toPrice = (value: FormDataEntryValue | undefined) =>
+  value === undefined || value === "" ? null : Number(value);
+
 function CreateEditForm({
   viewMode,
   item,
@@ -378,8 +414,11 @@ function CreateEditForm({
           product: {
             name: String(payload.name),
             category: String(payload.category),
+            sku: payload.sku ? String(payload.sku) : null,
             unit: String(payload.unit),
             current_stock: Number(payload.current_stock),
+            retail_price: toPrice(payload.retail_price),
+            wholesale_price: toPrice(payload.wholesale_price),
             note: payload.note ? String(payload.note) : null,
           },
         });
@@ -389,6 +428,9 @@ function CreateEditForm({
           req: {
             name: String(payload.name),
             category: String(payload.category),
+            sku: payload.sku ? String(payload.sku) : null,
+            retail_price: toPrice(payload.retail_price),
+            wholesale_price: toPrice(payload.wholesale_price),
             note: payload.note ? String(payload.note) : null,
           },
         });
@@ -396,7 +438,7 @@ function CreateEditForm({
 
       onFinish();
     } catch (err) {
-      alert(`異動失敗: ${err}`);
+      alert(`異動失敗: ${errorMessage(err)}`);
     }
   };
 
@@ -432,6 +474,18 @@ function CreateEditForm({
             ))}
           </select>
         </div>
+        <div className="col-span-2">
+          <label className="block text-sm font-bold text-soap-stone mb-2">
+            SKU
+          </label>
+          <input
+            name="sku"
+            type="text"
+            defaultValue={item?.sku ?? ""}
+            className="w-full border border-stone-200 rounded-lg"
+            placeholder="留空則自動產生"
+          />
+        </div>
         <div>
           <label className="block text-sm font-bold text-soap-stone mb-2">
             單位
@@ -463,6 +517,32 @@ function CreateEditForm({
             <p className="text-xs text-stone-400 mt-1">通過異動功能更新</p>
           )}
         </div>
+        <div>
+          <label className="block text-sm font-bold text-soap-stone mb-2">
+            零售價
+          </label>
+          <input
+            name="retail_price"
+            type="number"
+            min={0}
+            step="any"
+            defaultValue={item?.retail_price ?? ""}
+            className="w-full border border-stone-200 rounded-lg"
+          />
+        </div>
+        <div>
+          <label className="block text-sm font-bold text-soap-stone mb-2">
+            批發價
+          </label>
+          <input
+            name="wholesale_price"
+            type="number"
+            min={0}
+            step="any"
+            defaultValue={item?.wholesale_price ?? ""}
+            className="w-full border border-stone-200 rounded-lg"
+          />
+        </div>
         <div className="col-span-2">
           <label className="block text-sm font-bold text-soap-stone mb-2">
             備註
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import {
   Settings as SettingsIcon,
   Database,
@@ -7,17 +7,56 @@ import {
   Upload,
   Trash2,
   AlertCircle,
+  ShieldCheck,
+  PackageX,
 } from "lucide-react";
 
+import { LedgerReport } from "../types/type";
+import { errorMessage } from "../types/errors";
+
+const NEGATIVE_STOCK_POLICIES = [
+  { key: "reject", label: "禁止" },
+  { key: "warn", label: "允許並警告" },
+  { key: "allow", label: "允許" },
+];
+
+const NEGATIVE_STOCK_SETTINGS = [
+  { key: "material_negative_stock_policy", label: "原料" },
+  { key: "product_negative_stock_policy", label: "成品" },
+];
+
 export default function Settings() {
   const [error, setError] = useState<string | null>(null);
+  const [policies, setPolicies] = useState<Record<string, string>>({});
+
+  useEffect(() => {
+    const loadPolicies = async () => {
+      const entries = await Promise.all(
+        NEGATIVE_STOCK_SETTINGS.map(async ({ key }) => [
+          key,
+          (await invoke<string | null>("get_setting", { key })) ?? "warn",
+        ]),
+      );
+      setPolicies(Object.fromEntries(entries));
+    };
+    loadPolicies().catch((e) => console.log(`載入設定失敗: ${e}`));
+  }, []);
+
+  const handlePolicyChange = async (key: string, value: string) => {
+    try {
+      await invoke("set_setting", { key, value });
+      setPolicies((prev) => ({ ...prev, [key]: value }));
+    } catch (e) {
+      setError(`儲存設定失敗: ${errorMessage(e)}`);
+    }
+  };
 
   const handleExportDB = async () => {
     try {
       const path = await invoke<string>("export_database");
       console.log(`備份成功！檔案已存至：${path}`);
     } catch (e) {
-      setError(("備份失敗: " + e) as string);
+      setError(`備份失敗: ${errorMessage(e)}`);
       console.log(`備份失敗: ${e}`);
     } finally {
       setError(null);
@@ -29,7 +68,7 @@ export default function Settings() {
       const path = await invoke<string>("export_database_excel");
       console.log(`匯出成功！檔案已存至：${path}`);
     } catch (e) {
-      setError(("備份失敗: " + e) as string);
+      setError(`備份失敗: ${errorMessage(e)}`);
       console.log(`匯出失敗: ${e}`);
     } finally {
       setError(null);
@@ -41,13 +80,58 @@ export default function Settings() {
       const path = await invoke<string>("import_database");
       console.log(`匯入資料庫檔 ${path} 成功！`);
     } catch (e) {
-      setError(("備份失敗: " + e) as string);
+      setError(`備份失敗: ${errorMessage(e)}`);
       console.log(`匯入失敗: ${e}`);
     } finally {
       setError(null);
     }
   };
 
+  const handleVerifyLedger = async () => {
+    try {
+      const report = await invoke<LedgerReport>("verify_stock_ledger");
+      if (report.issues.length === 0) {
+        alert(
+          `帳務一致：已檢查 ${report.items_checked} 個項目、${report.logs_checked} 筆異動`,
+        );
+        return;
+      }
+
+      const lines = report.issues.map((i) => {
+        switch (i.kind) {
+          case "gap":
+            return `${i.item_name}：異動 #${i.inventory_log_id} 前庫存應為 ${i.expected}，紀錄為 ${i.actual}`;
+          case "row_mismatch":
+            return `${i.item_name}：異動 #${i.inventory_log_id} 後庫存應為 ${i.expected}，紀錄為 ${i.actual}`;
+          default:
+            return `${i.item_name}：帳上 ${i.expected}，目前庫存 ${i.actual}`;
+        }
+      });
+      const mismatches = report.issues.filter(
+        (i) => i.kind === "balance_mismatch",
+      ).length;
+      if (mismatches === 0) {
+        alert(`發現以下異動紀錄問題：\n${lines.join("\n")}`);
+        return;
+      }
+      if (
+        !confirm(
+          `發現以下帳務差異：\n${lines.join("\n")}\n\n是否以目前庫存為準，補登 ${mismatches} 筆校正紀錄？`,
+        )
+      ) {
+        return;
+      }
+
+      const repaired = await invoke<LedgerReport>("verify_stock_ledger", {
+        repair: true,
+      });
+      alert(`已補登 ${repaired.corrections.length} 筆校正紀錄`);
+    } catch (e) {
+      setError(`帳務檢查失敗: ${errorMessage(e)}`);
+      console.log(`帳務檢查失敗: ${e}`);
+    }
+  };
+
   return (
     <div className="max-w-4xl mx-auto space-y-8 pb-12">
       <div>
@@ -66,6 +150,34 @@ export default function Settings() {
 
       {/* 右側：實際設定內容 */}
       <div className="space-y-6">
+        {/* 庫存規則區塊 */}
+        <section className="bg-white rounded-2xl shadow-sm border border-stone-200 p-8 space-y-6 font-sans">
+          <div className="flex items-center gap-2 text-soap-stone font-bold border-b border-stone-100 pb-4">
+            <PackageX size={18} className="text-soap-wood" /> 負庫存規則
+          </div>
+
+          <div className="grid grid-cols-2 gap-4">
+            {NEGATIVE_STOCK_SETTINGS.map(({ key, label }) => (
+              <label key={key} className="space-y-2">
+                <p className="text-sm font-bold text-soap-stone">
+                  {label}出庫後低於零時
+                </p>
+                <select
+                  value={policies[key] ?? "warn"}
+                  onChange={(e) => handlePolicyChange(key, e.target.value)}
+                  className="w-full p-3 border border-stone-200 rounded-lg bg-white text-soap-stone"
+                >
+                  {NEGATIVE_STOCK_POLICIES.map((p) => (
+                    <option key={p.key} value={p.key}>
+                      {p.label}
+                    </option>
+                  ))}
+                </select>
+              </label>
+            ))}
+          </div>
+        </section>
+
         {/* 資料維護區塊 */}
         <section className="bg-white rounded-2xl shadow-sm border border-stone-200 p-8 space-y-6 font-sans">
           <div className="flex items-center gap-2 text-soap-stone font-bold border-b border-stone-100 pb-4">
@@ -113,6 +225,21 @@ export default function Settings() {
                 <p className="text-[10px] text-soap-accent">從備份檔還原</p>
               </div>
             </button>
+
+            <button
+              onClick={handleVerifyLedger}
+              className="p-4 border-2 border-stone-100 rounded-2xl flex flex-col items-center gap-3 hover:border-soap-wood hover:bg-stone-50 transition-all group"
+            >
+              <div className="p-3 bg-soap-beige rounded-full text-soap-wood group-hover:scale-110 transition-transform">
+                <ShieldCheck size={24} />
+              </div>
+              <div className="text-center">
+                <p className="font-bold text-soap-stone">帳務檢查</p>
+                <p className="text-[14px] text-soap-accent">
+                  核對異動紀錄與目前庫存
+                </p>
+              </div>
+            </button>
           </div>
 
           <div className="p-4 bg-red-50 rounded-xl flex items-start gap-3 border border-red-100">
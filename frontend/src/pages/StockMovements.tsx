@@ -13,7 +13,8 @@ import {
   Calendar,
 } from "lucide-react";
 
-import { Movement } from "../types/type";
+import { Movement, MovementResult } from "../types/type";
+import { errorMessage, shortageMessage } from "../types/errors";
 import MovementRow from "../components/MovementRow";
 
 type ViewMode = "list" | "create";
@@ -41,6 +42,23 @@ export default function StockMovements() {
     loadData();
   }, []);
 
+  const voidMovement = async (m: Movement) => {
+    const note = prompt(
+      `確定要作廢「${m.item_name}」的這筆異動嗎？\n作廢原因（選填）`,
+    );
+    if (note === null) return;
+    try {
+      const result = await invoke<MovementResult>("void_movement", {
+        id: m.id,
+        note: note || null,
+      });
+      if (result.warning) alert(shortageMessage(result.warning));
+      loadData();
+    } catch (err) {
+      alert(`作廢失敗: ${errorMessage(err)}`);
+    }
+  };
+
   const getFilteredMovements = () => {
     const now = new Date();
     const today = new Date(now.getFullYear(), now.getMonth(), now.getDate());
@@ -214,20 +232,23 @@ export default function StockMovements() {
                 <th className="px-6 py-4 text-right">數量</th>
                 <th className="px-6 py-4">關聯批次</th>
                 <th className="px-6 py-4">備註/原因</th>
+                <th className="px-6 py-4 text-center">操作</th>
               </tr>
             </thead>
             <tbody className="divide-y divide-stone-100 font-medium">
               {filteredMovements.length === 0 ? (
                 <tr>
                   <td
-                    colSpan={6}
+                    colSpan={7}
                     className="px-6 py-12 text-center text-stone-400"
                   >
                     尚無異動紀錄
                   </td>
                 </tr>
               ) : (
-                filteredMovements.map((m) => <MovementRow m={m} key={m.id} />)
+                filteredMovements.map((m) => (
+                  <MovementRow m={m} key={m.id} onVoid={voidMovement} />
+                ))
               )}
             </tbody>
           </table>
@@ -297,7 +318,7 @@ function MovementEntryForm({
       return;
     }
     try {
-      await invoke("add_inventory", {
+      const result = await invoke<MovementResult>("add_inventory", {
         req: {
           item_id: selectedItem.id,
           item_type: itemType,
@@ -306,9 +327,10 @@ function MovementEntryForm({
           note: note ?? null,
         },
       });
+      if (result.warning) alert(shortageMessage(result.warning));
       onFinish();
     } catch (err) {
-      alert(`異動失敗: ${err}`);
+      alert(`異動失敗: ${errorMessage(err)}`);
     }
   };
 
@@ -0,0 +1,45 @@
+import { StockShortage } from "./type";
+
+// Commands reject with { code, message, details }; commands that still return
+// plain string errors send the same shape as a JSON string
+export type ErrorCode =
+  | "not_found"
+  | "validation"
+  | "conflict"
+  | "insufficient_stock"
+  | "database"
+  | "io";
+
+export interface FieldError {
+  field: string;
+  message: string;
+}
+
+export interface CommandError {
+  code: ErrorCode;
+  message: string;
+  details?: unknown;
+}
+
+const isCommandError = (value: unknown): value is CommandError =>
+  typeof value === "object" &&
+  value !== null &&
+  typeof (value as CommandError).code === "string" &&
+  typeof (value as CommandError).message === "string";
+
+export const parseCommandError = (err: unknown): CommandError | null => {
+  if (isCommandError(err)) return err;
+  if (typeof err !== "string") return null;
+  try {
+    const parsed = JSON.parse(err);
+    return isCommandError(parsed) ? parsed : null;
+  } catch {
+    return null;
+  }
+};
+
+export const errorMessage = (err: unknown): string =>
+  parseCommandError(err)?.message ?? String(err);
+
+export const shortageMessage = (s: StockShortage): string =>
+  `「${s.item_name}」庫存已低於零：現有 ${s.current_stock}${s.unit}，出庫 ${s.requested}${s.unit}，不足 ${s.shortfall}${s.unit}`;
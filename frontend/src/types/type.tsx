@@ -2,9 +2,16 @@ export interface Material {
   id: number;
   name: string;
   category: string;
+  sku: string | null;
   unit: string;
   current_stock: number;
   low_stock_alert: number | null;
+  sap_naoh: number | null;
+  sap_koh: number | null;
+  /** g per ml, for materials stocked by volume */
+  density: number | null;
+  inci_name: string | null;
+  saponified_inci_name: string | null;
   note: string | null;
   created_at: string;
 }
@@ -13,25 +20,89 @@ export interface Product {
   id: number;
   name: string;
   category: string;
+  sku: string | null;
   unit: string;
   current_stock: number;
+  retail_price: number | null;
+  wholesale_price: number | null;
   note: string | null;
   created_at: string;
 }
 
+export interface CodeMatch {
+  item_type: ItemType;
+  item_id: number;
+  name: string;
+  sku: string | null;
+  unit: string;
+  current_stock: number;
+  supplier_name: string | null;
+}
+
+export interface ProductCost {
+  product_id: number;
+  recipe_id: number;
+  oils: number;
+  lye: number;
+  additives: number;
+  fragrance: number;
+  packaging: number;
+  labour: number;
+  total: number;
+  price: number | null;
+  margin: number | null;
+  margin_percent: number | null;
+}
+
+export type ItemType = "material" | "product";
+
+export type ActionType =
+  | "in"
+  | "out"
+  | "adj"
+  | "sale"
+  | "production"
+  | "waste"
+  | "return"
+  | "quarantine";
+
 export interface Movement {
   id: number;
   item_id: number;
   item_name: string;
-  item_type: "material" | "product";
+  item_type: ItemType;
   item_unit: string;
   change_amount: number;
-  action_type: "in" | "out" | "adj";
+  action_type: ActionType;
+  target_stock: number | null;
   related_batch: string | null;
   note: string | null;
+  batch_id: number | null;
+  purchase_order_id: number | null;
+  sales_order_id: number | null;
+  stocktake_session_id: number | null;
+  reversal_of: number | null;
+  voided_at: string | null;
+  voided_by: number | null;
+  void_of: number | null;
   created_at: string;
 }
 
+export interface StockShortage {
+  item_type: ItemType;
+  item_id: number;
+  item_name: string;
+  unit: string;
+  current_stock: number;
+  requested: number;
+  shortfall: number;
+}
+
+export interface MovementResult {
+  movement: Movement;
+  warning: StockShortage | null;
+}
+
 export const MOVEMENT_ACTIONS = {
   in: {
     key: "in",
@@ -54,3 +125,20 @@ export type ViewMode = "list" | "add" | "edit" | "movement";
 
 export const MATERIAL_UNIT_LIST = ["mL", "L", "g", "kg"] as const;
 export const PRODUCT_UNIT_LIST = ["個", "盒", "組", "瓶"] as const;
+
+export interface LedgerIssue {
+  kind: "gap" | "row_mismatch" | "balance_mismatch";
+  item_type: ItemType;
+  item_id: number;
+  item_name: string;
+  inventory_log_id: number | null;
+  expected: number;
+  actual: number;
+}
+
+export interface LedgerReport {
+  items_checked: number;
+  logs_checked: number;
+  issues: LedgerIssue[];
+  corrections: number[];
+}
@@ -0,0 +1,47 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Print resolution of the saved PNG sheets */
+const DPI = 300;
+/** The sheets are sized in points, which the webview lays out at 96 px per inch */
+const CSS_PIXELS_PER_INCH = 96;
+
+/** Draws an SVG sheet onto a canvas and returns it PNG-encoded */
+async function rasterise(svg: string): Promise<number[]> {
+  const url = URL.createObjectURL(new Blob([svg], { type: "image/svg+xml" }));
+  try {
+    const image = new Image();
+    image.src = url;
+    await image.decode();
+
+    const scale = DPI / CSS_PIXELS_PER_INCH;
+    const canvas = document.createElement("canvas");
+    canvas.width = Math.round(image.width * scale);
+    canvas.height = Math.round(image.height * scale);
+    const context = canvas.getContext("2d");
+    if (!context) throw new Error("無法建立繪圖區");
+    context.fillStyle = "white";
+    context.fillRect(0, 0, canvas.width, canvas.height);
+    context.drawImage(image, 0, 0, canvas.width, canvas.height);
+
+    const blob = await new Promise<Blob | null>((resolve) =>
+      canvas.toBlob(resolve, "image/png"),
+    );
+    if (!blob) throw new Error("標籤圖檔產生失敗");
+    return Array.from(new Uint8Array(await blob.arrayBuffer()));
+  } finally {
+    URL.revokeObjectURL(url);
+  }
+}
+
+/**
+ * Saves the label sheets of a production batch as PNG files. The backend lays
+ * the sheets out as SVG; the webview rasterises them with its CJK fonts.
+ */
+export async function exportLabelPng(
+  batchId: number,
+  copies?: number,
+): Promise<string> {
+  const svgs = await invoke<string[]>("get_label_sheets", { batchId, copies });
+  const sheets = await Promise.all(svgs.map(rasterise));
+  return invoke<string>("save_label_png", { batchId, sheets });
+}
@@ -0,0 +1,107 @@
+use crate::barcode::Encoded;
+
+/// Bar and space widths of every Code 128 symbol value, bar first
+const PATTERNS: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232",
+];
+const STOP: &str = "2331112";
+
+const START_B: u8 = 104;
+const START_C: u8 = 105;
+
+/// Encodes printable ASCII with code set B, or an even number of digits with
+/// the denser code set C. The check symbol is added automatically.
+pub(crate) fn encode(code: &str) -> Result<Encoded, String> {
+    if code.is_empty() || !code.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+        return Err(format!("Code 128 條碼只能包含英數字與符號：{}", code));
+    }
+
+    let use_c = code.len() % 2 == 0 && code.bytes().all(|b| b.is_ascii_digit());
+    let mut values = if use_c {
+        let mut values = vec![START_C];
+        values.extend(
+            code.as_bytes()
+                .chunks(2)
+                .map(|pair| (pair[0] - b'0') * 10 + (pair[1] - b'0')),
+        );
+        values
+    } else {
+        let mut values = vec![START_B];
+        values.extend(code.bytes().map(|b| b - b' '));
+        values
+    };
+
+    values.push(checksum(&values));
+
+    let mut modules = Vec::with_capacity(values.len() * 11 + 13);
+    for value in values {
+        push_widths(&mut modules, PATTERNS[value as usize]);
+    }
+    push_widths(&mut modules, STOP);
+
+    Ok(Encoded {
+        modules,
+        text: code.to_string(),
+        quiet_zone: 10,
+    })
+}
+
+/// Check symbol of a start symbol and the data values that follow it
+fn checksum(values: &[u8]) -> u8 {
+    let sum: usize = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| value as usize * i.max(1))
+        .sum();
+    (sum % 103) as u8
+}
+
+/// Appends alternating bars and spaces of the given widths, starting with a bar
+fn push_widths(modules: &mut Vec<bool>, widths: &str) {
+    for (i, width) in widths.bytes().enumerate() {
+        let bar = i % 2 == 0;
+        modules.extend(std::iter::repeat(bar).take((width - b'0') as usize));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_set_b() {
+        let mut values = vec![START_B];
+        values.extend(b"Wikipedia".iter().map(|b| b - b' '));
+        assert_eq!(checksum(&values), 88);
+    }
+
+    #[test]
+    fn checksum_set_c() {
+        assert_eq!(checksum(&[START_C, 12, 34, 56]), 44);
+    }
+
+    #[test]
+    fn encodes_digits_with_set_c() {
+        let encoded = encode("123456").unwrap();
+        // Start, three digit pairs and the check symbol are 11 modules each, the stop 13
+        assert_eq!(encoded.modules.len(), 5 * 11 + 13);
+        assert_eq!(encoded.text, "123456");
+    }
+
+    #[test]
+    fn rejects_non_ascii() {
+        assert!(encode("皂123").is_err());
+        assert!(encode("").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::barcode::Encoded;
+
+/// Left-hand digits with odd parity
+const L_CODES: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+/// Left-hand digits with even parity
+const G_CODES: [&str; 10] = [
+    "0100111", "0110011", "0011011", "0100001", "0011101", "0111001", "0000101", "0010001",
+    "0001001", "0010111",
+];
+/// Right-hand digits
+const R_CODES: [&str; 10] = [
+    "1110010", "1100110", "1101100", "1000010", "1011100", "1001110", "1010000", "1000100",
+    "1001000", "1110100",
+];
+/// Parity of the six left-hand digits, chosen by the first digit
+const PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+
+/// Check digit of the first twelve digits of an EAN-13
+pub(crate) fn check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .take(12)
+        .enumerate()
+        .map(|(i, &d)| d as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// Encodes 12 digits, adding the check digit, or 13 digits whose check digit is verified
+pub(crate) fn encode(code: &str) -> Result<Encoded, String> {
+    if !code.chars().all(|c| c.is_ascii_digit()) || !(12..=13).contains(&code.len()) {
+        return Err(format!("EAN-13 條碼必須是 12 或 13 位數字：{}", code));
+    }
+
+    let mut digits: Vec<u8> = code.bytes().map(|b| b - b'0').collect();
+    let check = check_digit(&digits);
+    match digits.get(12) {
+        Some(&given) if given != check => {
+            return Err(format!("EAN-13 條碼 {} 的檢查碼錯誤，應為 {}", code, check))
+        }
+        Some(_) => {}
+        None => digits.push(check),
+    }
+
+    let mut pattern = String::from("101");
+    let parity = PARITY[digits[0] as usize];
+    for (&digit, side) in digits[1..7].iter().zip(parity.chars()) {
+        let codes = if side == 'L' { &L_CODES } else { &G_CODES };
+        pattern.push_str(codes[digit as usize]);
+    }
+    pattern.push_str("01010");
+    for &digit in &digits[7..] {
+        pattern.push_str(R_CODES[digit as usize]);
+    }
+    pattern.push_str("101");
+
+    Ok(Encoded {
+        modules: pattern.chars().map(|c| c == '1').collect(),
+        text: digits.iter().map(|d| (b'0' + d) as char).collect(),
+        quiet_zone: 11,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(code: &str) -> Vec<u8> {
+        code.bytes().map(|b| b - b'0').collect()
+    }
+
+    #[test]
+    fn check_digit_of_known_code() {
+        assert_eq!(check_digit(&digits("400638133393")), 1);
+    }
+
+    #[test]
+    fn encode_appends_check_digit() {
+        let encoded = encode("400638133393").unwrap();
+        assert_eq!(encoded.text, "4006381333931");
+        assert_eq!(encoded.modules.len(), 95);
+    }
+
+    #[test]
+    fn encode_verifies_given_check_digit() {
+        assert!(encode("4006381333931").is_ok());
+        assert!(encode("4006381333932").is_err());
+        assert!(encode("40063813339").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+pub mod code128;
+pub mod ean13;
+
+use crate::models::barcode::BarcodeFormat;
+
+/// Module width in SVG user units and PNG pixels
+const MODULE: u32 = 2;
+const BAR_HEIGHT: u32 = 70;
+const TEXT_SIZE: u32 = 14;
+
+/// A barcode as a row of modules, `true` for a bar
+pub(crate) struct Encoded {
+    pub modules: Vec<bool>,
+    /// Human-readable text printed under the bars
+    pub text: String,
+    /// Blank modules required on each side
+    pub quiet_zone: u32,
+}
+
+/// Picks EAN-13 for 12 or 13 digit codes and Code 128 for everything else
+pub(crate) fn detect_format(code: &str) -> BarcodeFormat {
+    if (12..=13).contains(&code.len()) && code.bytes().all(|b| b.is_ascii_digit()) {
+        BarcodeFormat::Ean13
+    } else {
+        BarcodeFormat::Code128
+    }
+}
+
+pub(crate) fn encode(code: &str, format: BarcodeFormat) -> Result<Encoded, String> {
+    match format {
+        BarcodeFormat::Ean13 => ean13::encode(code),
+        BarcodeFormat::Code128 => code128::encode(code),
+    }
+}
+
+impl Encoded {
+    fn width(&self) -> u32 {
+        (self.modules.len() as u32 + self.quiet_zone * 2) * MODULE
+    }
+
+    /// Runs of bars as (first module, length)
+    fn bars(&self) -> Vec<(u32, u32)> {
+        let mut bars = Vec::new();
+        let mut start = None;
+        for (i, &bar) in self.modules.iter().chain([&false]).enumerate() {
+            match (bar, start) {
+                (true, None) => start = Some(i as u32),
+                (false, Some(first)) => {
+                    bars.push((first, i as u32 - first));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        bars
+    }
+
+    pub(crate) fn to_svg(&self) -> String {
+        let width = self.width();
+        let height = BAR_HEIGHT + TEXT_SIZE + 6;
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+        svg.push_str(&format!(
+            r#"<rect width="{width}" height="{height}" fill="white"/>"#
+        ));
+        for (first, length) in self.bars() {
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="0" width="{}" height="{}" fill="black"/>"#,
+                (first + self.quiet_zone) * MODULE,
+                length * MODULE,
+                BAR_HEIGHT
+            ));
+        }
+        svg.push_str(&format!(
+            r#"<text x="{}" y="{}" font-family="monospace" font-size="{}" text-anchor="middle">{}</text>"#,
+            width / 2,
+            BAR_HEIGHT + TEXT_SIZE + 2,
+            TEXT_SIZE,
+            escape_xml(&self.text)
+        ));
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Renders the bars alone as a grayscale PNG; the text is left to the printing software
+    pub(crate) fn to_png(&self) -> Result<Vec<u8>, String> {
+        let width = self.width();
+        let mut row = vec![255u8; width as usize];
+        for (first, length) in self.bars() {
+            let start = ((first + self.quiet_zone) * MODULE) as usize;
+            row[start..start + (length * MODULE) as usize].fill(0);
+        }
+
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width, BAR_HEIGHT);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer
+            .write_image_data(&row.repeat(BAR_HEIGHT as usize))
+            .map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+
+        Ok(png)
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,321 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+use tauri_plugin_dialog::{DialogExt, FilePath};
+
+use crate::barcode;
+use crate::commands::settings_cmd::{read_setting, MATERIAL_SKU_PATTERN, PRODUCT_SKU_PATTERN};
+use crate::error::AppError;
+use crate::models::barcode::{Barcode, BarcodeFormat, BarcodeImageType, CodeMatch};
+
+/// Which table a SKU belongs to
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SkuOwner {
+    Product,
+    Material,
+}
+
+impl SkuOwner {
+    fn table(self) -> &'static str {
+        match self {
+            SkuOwner::Product => "products",
+            SkuOwner::Material => "materials",
+        }
+    }
+
+    fn pattern_key(self) -> &'static str {
+        match self {
+            SkuOwner::Product => PRODUCT_SKU_PATTERN,
+            SkuOwner::Material => MATERIAL_SKU_PATTERN,
+        }
+    }
+
+    fn default_pattern(self) -> &'static str {
+        match self {
+            SkuOwner::Product => "P{id:05}",
+            SkuOwner::Material => "M{id:05}",
+        }
+    }
+}
+
+/// Looks up the product or material a scanned code belongs to. Matches product
+/// and material SKUs, then supplier item codes printed on material packaging.
+/// A 13-digit EAN is also tried without its check digit.
+#[tauri::command]
+pub async fn find_item_by_code(
+    pool: State<'_, SqlitePool>,
+    code: String,
+) -> Result<Option<CodeMatch>, String> {
+    let code = code.trim();
+    if code.is_empty() {
+        return Ok(None);
+    }
+
+    let mut candidates = vec![code];
+    if barcode::detect_format(code) == BarcodeFormat::Ean13 && code.len() == 13 {
+        candidates.push(&code[..12]);
+    }
+
+    for candidate in candidates {
+        let found = sqlx::query_as::<_, CodeMatch>(
+            "SELECT 'product' as item_type, id as item_id, name, sku, unit,
+                    CAST(current_stock AS REAL) as current_stock, NULL as supplier_name
+             FROM products
+             WHERE sku = ? COLLATE NOCASE AND deleted_at IS NULL
+             UNION ALL
+             SELECT 'material', id, name, sku, unit, current_stock, NULL
+             FROM materials
+             WHERE sku = ? COLLATE NOCASE AND deleted_at IS NULL
+             UNION ALL
+             SELECT 'material', m.id, m.name, m.sku, m.unit, m.current_stock, s.name
+             FROM material_suppliers ms
+             JOIN materials m ON ms.material_id = m.id
+             JOIN suppliers s ON ms.supplier_id = s.id
+             WHERE ms.item_code = ? COLLATE NOCASE AND m.deleted_at IS NULL
+             LIMIT 1",
+        )
+        .bind(candidate)
+        .bind(candidate)
+        .bind(candidate)
+        .fetch_optional(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Renders a code as an SVG barcode. The format is picked from the code when not given.
+#[tauri::command]
+pub async fn generate_barcode(
+    code: String,
+    format: Option<BarcodeFormat>,
+) -> Result<Barcode, String> {
+    let code = code.trim();
+    let format = format.unwrap_or_else(|| barcode::detect_format(code));
+    let encoded = barcode::encode(code, format)?;
+
+    Ok(Barcode {
+        format,
+        svg: encoded.to_svg(),
+        value: encoded.text,
+    })
+}
+
+/// Saves a barcode as an SVG or PNG file where the user picks
+#[tauri::command]
+pub async fn export_barcode(
+    app: tauri::AppHandle,
+    code: String,
+    format: Option<BarcodeFormat>,
+    image_type: BarcodeImageType,
+) -> Result<String, String> {
+    let code = code.trim();
+    let format = format.unwrap_or_else(|| barcode::detect_format(code));
+    let encoded = barcode::encode(code, format)?;
+
+    let (bytes, extension) = match image_type {
+        BarcodeImageType::Svg => (encoded.to_svg().into_bytes(), "svg"),
+        BarcodeImageType::Png => (encoded.to_png()?, "png"),
+    };
+
+    let export_file = app
+        .dialog()
+        .file()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(format!("barcode_{}.{}", encoded.text, extension))
+        .blocking_save_file();
+
+    let export_path = match export_file {
+        Some(FilePath::Path(p)) => p.display().to_string(),
+        _ => return Err("No file path selected".to_string()),
+    };
+
+    std::fs::write(&export_path, bytes).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(export_path)
+}
+
+/// Stores `requested` as the SKU of a newly added item, or one generated from
+/// the owner's SKU pattern when it is blank
+pub(crate) async fn assign_sku(
+    conn: &mut SqliteConnection,
+    owner: SkuOwner,
+    id: i64,
+    requested: Option<&str>,
+) -> Result<(), AppError> {
+    let sku = match requested.map(str::trim).filter(|sku| !sku.is_empty()) {
+        Some(sku) => sku.to_string(),
+        None => {
+            let pattern = read_setting(conn, owner.pattern_key())
+                .await
+                .map_err(AppError::Database)?
+                .filter(|pattern| !pattern.trim().is_empty())
+                .unwrap_or_else(|| owner.default_pattern().to_string());
+            expand_sku_pattern(&pattern, id, chrono::Local::now().date_naive())
+                .map_err(AppError::validation)?
+        }
+    };
+
+    write_sku(conn, owner, id, &sku).await
+}
+
+/// Changes an item's SKU; a blank SKU leaves the current one in place
+pub(crate) async fn update_sku(
+    conn: &mut SqliteConnection,
+    owner: SkuOwner,
+    id: i64,
+    requested: Option<&str>,
+) -> Result<(), AppError> {
+    match requested.map(str::trim).filter(|sku| !sku.is_empty()) {
+        Some(sku) => write_sku(conn, owner, id, sku).await,
+        None => Ok(()),
+    }
+}
+
+async fn write_sku(
+    conn: &mut SqliteConnection,
+    owner: SkuOwner,
+    id: i64,
+    sku: &str,
+) -> Result<(), AppError> {
+    if !sku.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+        return Err(AppError::invalid_field(
+            "sku",
+            format!("SKU 只能使用英數字與符號：{}", sku),
+        ));
+    }
+
+    // Scanners may send either case, so codes must differ by more than case
+    let sql = format!(
+        "SELECT EXISTS(SELECT 1 FROM {} WHERE sku = ? COLLATE NOCASE AND id != ?)",
+        owner.table()
+    );
+    let taken: bool = sqlx::query_scalar(&sql)
+        .bind(sku)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    if taken {
+        return Err(AppError::Conflict(format!("SKU「{}」已被使用", sku)));
+    }
+
+    let sql = format!("UPDATE {} SET sku = ? WHERE id = ?", owner.table());
+    sqlx::query(&sql)
+        .bind(sku)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Fills `{id}`, `{yyyy}`, `{yy}`, `{mm}` and `{dd}` into a SKU pattern. Numbers
+/// take a zero-padded width after a colon, e.g. `{id:05}`.
+fn expand_sku_pattern(pattern: &str, id: i64, date: chrono::NaiveDate) -> Result<String, String> {
+    if !pattern.contains("{id") {
+        return Err(format!("SKU 格式必須包含 {{id}}：{}", pattern));
+    }
+
+    let mut sku = String::new();
+    let mut rest = pattern;
+    while let Some(open) = rest.find('{') {
+        sku.push_str(&rest[..open]);
+        let close = rest[open..]
+            .find('}')
+            .map(|close| open + close)
+            .ok_or_else(|| format!("SKU 格式缺少 }}：{}", pattern))?;
+
+        let field = &rest[open + 1..close];
+        let (name, width) = match field.split_once(':') {
+            Some((name, width)) => (
+                name,
+                width
+                    .parse::<usize>()
+                    .map_err(|_| format!("SKU 格式的寬度不是數字：{}", field))?,
+            ),
+            None => (field, 0),
+        };
+        let value = match name {
+            "id" => id.to_string(),
+            "yyyy" => date.format("%Y").to_string(),
+            "yy" => date.format("%y").to_string(),
+            "mm" => date.format("%m").to_string(),
+            "dd" => date.format("%d").to_string(),
+            _ => return Err(format!("SKU 格式中有未知的欄位 {{{}}}", name)),
+        };
+        sku.push_str(&format!("{:0>width$}", value, width = width));
+
+        rest = &rest[close + 1..];
+    }
+    sku.push_str(rest);
+
+    Ok(sku)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Connection;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn expands_padded_sequence_and_date() {
+        let sku = expand_sku_pattern("SOAP-{yy}{mm}{dd}-{id:04}", 7, date(2024, 3, 5)).unwrap();
+        assert_eq!(sku, "SOAP-240305-0007");
+        assert_eq!(
+            expand_sku_pattern("{yyyy}/{id}", 12345, date(2024, 3, 5)).unwrap(),
+            "2024/12345"
+        );
+        // The width is a minimum; longer ids are never cut
+        assert_eq!(
+            expand_sku_pattern("P{id:03}", 12345, date(2024, 3, 5)).unwrap(),
+            "P12345"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let today = date(2024, 3, 5);
+        assert!(expand_sku_pattern("P-{yyyy}", 1, today).is_err());
+        assert!(expand_sku_pattern("P{id", 1, today).is_err());
+        assert!(expand_sku_pattern("P{id:x}", 1, today).is_err());
+        assert!(expand_sku_pattern("P{id}-{week}", 1, today).is_err());
+    }
+
+    #[tokio::test]
+    async fn generated_sku_colliding_with_existing_one_is_a_conflict() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE app_settings (key TEXT PRIMARY KEY, value TEXT);
+             CREATE TABLE products (id INTEGER PRIMARY KEY, sku TEXT);
+             INSERT INTO products (id, sku) VALUES (1, 'p00002'), (2, NULL), (3, NULL);",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        // Product 1 was given product 2's default code by hand, in lower case
+        let err = assign_sku(&mut conn, SkuOwner::Product, 2, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "conflict");
+
+        assign_sku(&mut conn, SkuOwner::Product, 3, None)
+            .await
+            .unwrap();
+        let sku: Option<String> = sqlx::query_scalar("SELECT sku FROM products WHERE id = 3")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(sku.as_deref(), Some("P00003"));
+    }
+}
@@ -0,0 +1,312 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+
+use crate::commands::formula_cmd::{quantity_from_grams, recipe_lye, OIL_CATEGORY};
+use crate::commands::recipe_cmd::fetch_recipe;
+use crate::commands::settings_cmd::{read_number_setting, LABOUR_COST_PER_BATCH};
+use crate::models::cost::{
+    CategoryValuation, CostCategory, CostLine, InventoryValuation, MaterialValuation, ProductCost,
+};
+
+const FRAGRANCE_CATEGORY: &str = "精油 / 香精";
+/// Packaging material categories: the material form saves 包裝原料, while the
+/// material filter lists 包裝材料
+const PACKAGING_CATEGORIES: [&str; 2] = ["包裝材料", "包裝原料"];
+
+/// Values material stock at its moving weighted-average cost
+#[tauri::command]
+pub async fn inventory_valuation(
+    pool: State<'_, SqlitePool>,
+) -> Result<InventoryValuation, String> {
+    compute_valuation(&pool).await
+}
+
+pub(crate) async fn compute_valuation(pool: &SqlitePool) -> Result<InventoryValuation, String> {
+    let materials = sqlx::query_as::<_, MaterialValuation>(
+        "SELECT id as material_id, name as material_name, COALESCE(category, '') as category,
+                unit, current_stock as quantity, avg_cost,
+                COALESCE(current_stock * avg_cost, 0.0) as value
+         FROM materials
+         WHERE deleted_at IS NULL
+         ORDER BY category, name",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Materials are sorted by category, so each category is one contiguous run
+    let mut categories: Vec<CategoryValuation> = Vec::new();
+    for material in &materials {
+        match categories.last_mut() {
+            Some(last) if last.category == material.category => last.value += material.value,
+            _ => categories.push(CategoryValuation {
+                category: material.category.clone(),
+                value: material.value,
+            }),
+        }
+    }
+
+    let total_value = categories.iter().map(|category| category.value).sum();
+    let uncosted_count = materials
+        .iter()
+        .filter(|material| material.avg_cost.is_none() && material.quantity > 0.0)
+        .count() as i64;
+
+    Ok(InventoryValuation {
+        materials,
+        categories,
+        total_value,
+        uncosted_count,
+    })
+}
+
+/// Rolls up the cost of one unit of a product from a recipe's material average
+/// costs plus labour and overhead per batch. Uses the product's first recipe
+/// when `recipe_id` is not given. The margin is taken against `price`, else the
+/// retail price, else the last selling price.
+#[tauri::command]
+pub async fn get_product_cost(
+    pool: State<'_, SqlitePool>,
+    product_id: i64,
+    recipe_id: Option<i64>,
+    price: Option<f64>,
+) -> Result<ProductCost, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    compute_product_cost(&mut conn, product_id, recipe_id, price).await
+}
+
+pub(crate) async fn compute_product_cost(
+    conn: &mut SqliteConnection,
+    product_id: i64,
+    recipe_id: Option<i64>,
+    price: Option<f64>,
+) -> Result<ProductCost, String> {
+    let recipe_id = match recipe_id {
+        Some(recipe_id) => recipe_id,
+        None => {
+            let first_recipe: Option<i64> =
+                sqlx::query_scalar("SELECT MIN(id) FROM recipes WHERE product_id = ?")
+                    .bind(product_id)
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            first_recipe.ok_or_else(|| format!("產品 id {} 尚未設定配方", product_id))?
+        }
+    };
+
+    let recipe = fetch_recipe(conn, recipe_id).await?;
+    if recipe.recipe.product_id != product_id {
+        return Err(format!(
+            "配方「{}」不屬於產品 id {}",
+            recipe.recipe.name, product_id
+        ));
+    }
+
+    // Lye is costed by the calculated amount rather than a fixed recipe line
+    let lye = recipe_lye(conn, &recipe).await?;
+    let lye_material_id = lye.as_ref().map(|(material_id, _)| *material_id);
+
+    let mut usages: Vec<(i64, f64)> = recipe
+        .lines
+        .iter()
+        .filter(|line| Some(line.material_id) != lye_material_id)
+        .map(|line| (line.material_id, line.quantity))
+        .collect();
+    if let Some((material_id, result)) = &lye {
+        // The calculated lye is in grams; its average cost is per unit of the lye material
+        let (lye_name, unit, density): (String, String, Option<f64>) =
+            sqlx::query_as("SELECT name, unit, density FROM materials WHERE id = ?")
+                .bind(material_id)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        let quantity = quantity_from_grams(result.lye_weight, &unit, density)
+            .ok_or_else(|| format!("鹼「{}」以 {} 記錄，無法換算為重量", lye_name, unit))?;
+        usages.push((*material_id, quantity));
+    }
+
+    let mut lines = Vec::with_capacity(usages.len());
+    for (material_id, quantity) in usages {
+        lines.push(cost_line(conn, material_id, quantity, lye_material_id).await?);
+    }
+
+    let yield_quantity = recipe.recipe.yield_quantity as f64;
+    let per_unit = |category: CostCategory| {
+        lines
+            .iter()
+            .filter(|line| line.category == category)
+            .map(|line| line.cost)
+            .sum::<f64>()
+            / yield_quantity
+    };
+    let oils = per_unit(CostCategory::Oils);
+    let lye = per_unit(CostCategory::Lye);
+    let additives = per_unit(CostCategory::Additives);
+    let fragrance = per_unit(CostCategory::Fragrance);
+    let packaging = per_unit(CostCategory::Packaging);
+
+    let labour_per_batch = read_number_setting(conn, LABOUR_COST_PER_BATCH)
+        .await?
+        .unwrap_or(0.0);
+    let labour = labour_per_batch / yield_quantity;
+
+    let total = oils + lye + additives + fragrance + packaging + labour;
+
+    let price = match price {
+        Some(price) => Some(price),
+        None => match retail_price(conn, product_id).await? {
+            Some(price) => Some(price),
+            None => last_selling_price(conn, product_id).await?,
+        },
+    };
+    let margin = price.map(|price| price - total);
+    let margin_percent = price
+        .zip(margin)
+        .filter(|(price, _)| *price > 0.0)
+        .map(|(price, margin)| margin / price * 100.0);
+
+    let uncosted_materials = lines
+        .iter()
+        .filter(|line| line.avg_cost.is_none())
+        .map(|line| line.material_name.clone())
+        .collect();
+
+    Ok(ProductCost {
+        product_id,
+        product_name: recipe.recipe.product_name,
+        recipe_id,
+        recipe_name: recipe.recipe.name,
+        yield_quantity: recipe.recipe.yield_quantity,
+        oils,
+        lye,
+        additives,
+        fragrance,
+        packaging,
+        labour,
+        total,
+        batch_cost: total * yield_quantity,
+        lines,
+        uncosted_materials,
+        price,
+        margin,
+        margin_percent,
+    })
+}
+
+async fn cost_line(
+    conn: &mut SqliteConnection,
+    material_id: i64,
+    quantity: f64,
+    lye_material_id: Option<i64>,
+) -> Result<CostLine, String> {
+    let (material_name, category, unit, avg_cost): (String, Option<String>, String, Option<f64>) =
+        sqlx::query_as("SELECT name, category, unit, avg_cost FROM materials WHERE id = ?")
+            .bind(material_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("查無原料 id {}", material_id))?;
+
+    let category = match category.as_deref().unwrap_or_default() {
+        _ if Some(material_id) == lye_material_id => CostCategory::Lye,
+        OIL_CATEGORY => CostCategory::Oils,
+        FRAGRANCE_CATEGORY => CostCategory::Fragrance,
+        category if PACKAGING_CATEGORIES.contains(&category) => CostCategory::Packaging,
+        _ => CostCategory::Additives,
+    };
+
+    Ok(CostLine {
+        material_id,
+        material_name,
+        category,
+        quantity,
+        unit,
+        avg_cost,
+        cost: quantity * avg_cost.unwrap_or(0.0),
+    })
+}
+
+async fn retail_price(conn: &mut SqliteConnection, product_id: i64) -> Result<Option<f64>, String> {
+    let price: Option<Option<f64>> =
+        sqlx::query_scalar("SELECT retail_price FROM products WHERE id = ?")
+            .bind(product_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    Ok(price.flatten())
+}
+
+/// Price of the product on its most recent sale
+async fn last_selling_price(
+    conn: &mut SqliteConnection,
+    product_id: i64,
+) -> Result<Option<f64>, String> {
+    sqlx::query_scalar(
+        "SELECT si.price FROM sales_items si
+         JOIN sales_orders so ON si.order_id = so.id
+         WHERE si.product_id = ? AND so.status != 'cancelled'
+         ORDER BY so.order_date DESC, si.id DESC
+         LIMIT 1",
+    )
+    .bind(product_id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::db::migrate::migrate;
+
+    #[tokio::test]
+    async fn lye_is_costed_in_the_lye_materials_unit() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        migrate(&pool).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        // Seeded NaOH (id 3) restocked by the kilogram at 200 per kg
+        sqlx::query("UPDATE materials SET unit = 'kg', avg_cost = 200 WHERE id = 3")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO recipes (id, product_id, name, yield_quantity, created_at,
+                                  lye_material_id, lye_type, superfat, water_ratio)
+             VALUES (1, 2, '橄欖皂', 10, datetime('now'), 3, 'naoh', 5, 2.5);
+             INSERT INTO recipe_lines (recipe_id, material_id, quantity) VALUES (1, 1, 1000);",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+
+        let cost = compute_product_cost(&mut conn, 2, Some(1), None)
+            .await
+            .unwrap();
+        // 1000 ml of olive oil at 0.91 g/ml, SAP 0.1345, 5% superfat
+        let lye_grams = 1000.0 * 0.91 * 0.1345 * 0.95;
+        let line = cost
+            .lines
+            .iter()
+            .find(|line| line.material_id == 3)
+            .unwrap();
+        assert!((line.quantity - lye_grams / 1000.0).abs() < 1e-9);
+        assert!((cost.lye - lye_grams / 1000.0 * 200.0 / 10.0).abs() < 1e-9);
+
+        sqlx::query("UPDATE materials SET unit = 'pcs' WHERE id = 3")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        assert!(compute_product_cost(&mut conn, 2, Some(1), None)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use sqlx::SqlitePool;
+use tauri::State;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+
+use crate::models::customer::{
+    CreateCustomerRequest, Customer, CustomerHistory, CustomerPurchase, RemoveCustomerRequest,
+    UpdateCustomerRequest,
+};
+
+#[tauri::command]
+pub async fn list_customers(pool: State<'_, SqlitePool>) -> Result<Vec<Customer>, String> {
+    let rows = sqlx::query_as::<_, Customer>(
+        "SELECT id, name, phone, email, price_tier, note, created_at, deleted_at
+         FROM customers
+         WHERE deleted_at IS NULL
+         ORDER BY name",
+    )
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn add_customer(
+    pool: State<'_, SqlitePool>,
+    customer: CreateCustomerRequest,
+) -> Result<(), String> {
+    if customer.name.trim().is_empty() {
+        return Err("客戶名稱不可為空".to_string());
+    }
+
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO customers
+        (name, phone, email, price_tier, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&customer.name)
+    .bind(&customer.phone)
+    .bind(&customer.email)
+    .bind(customer.price_tier)
+    .bind(&customer.note)
+    .bind(&created_at)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_customer(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    req: UpdateCustomerRequest,
+) -> Result<(), String> {
+    if req.name.trim().is_empty() {
+        return Err("客戶名稱不可為空".to_string());
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE customers
+        SET name = ?, phone = ?, email = ?, price_tier = ?, note = ?
+        WHERE id = ? AND deleted_at IS NULL
+        "#,
+    )
+    .bind(&req.name)
+    .bind(&req.phone)
+    .bind(&req.email)
+    .bind(req.price_tier)
+    .bind(&req.note)
+    .bind(id)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("查無客戶 id {}", id));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_customer(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    customer: RemoveCustomerRequest,
+) -> Result<(), String> {
+    let answer = app
+        .dialog()
+        .message("確定要刪除這個客戶嗎？")
+        .title("確認刪除")
+        .buttons(MessageDialogButtons::OkCancel)
+        .blocking_show();
+
+    if !answer {
+        return Ok(());
+    }
+
+    let deleted_at = chrono::Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        UPDATE customers
+        SET deleted_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&deleted_at)
+    .bind(customer.id)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Everything a customer has bought, newest order first. Cancelled orders are
+/// left out and refunds are netted from the total. Removed customers keep their
+/// history.
+#[tauri::command]
+pub async fn get_customer_history(
+    pool: State<'_, SqlitePool>,
+    customer_id: i64,
+) -> Result<CustomerHistory, String> {
+    let customer = sqlx::query_as::<_, Customer>(
+        "SELECT id, name, phone, email, price_tier, note, created_at, deleted_at
+         FROM customers
+         WHERE id = ?",
+    )
+    .bind(customer_id)
+    .fetch_optional(&*pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("查無客戶 id {}", customer_id))?;
+
+    let purchases = sqlx::query_as::<_, CustomerPurchase>(
+        "SELECT so.id as order_id, so.order_date, si.product_id, p.name as product_name,
+                si.quantity, si.price, si.quantity * si.price as subtotal
+         FROM sales_orders so
+         JOIN sales_items si ON si.order_id = so.id
+         JOIN products p ON si.product_id = p.id
+         WHERE so.customer_id = ? AND so.status != 'cancelled'
+         ORDER BY so.order_date DESC, so.id DESC, si.id",
+    )
+    .bind(customer_id)
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut order_ids: Vec<i64> = purchases.iter().map(|item| item.order_id).collect();
+    order_ids.dedup();
+    let refunds: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(refund_amount), 0.0) FROM sales_orders
+         WHERE customer_id = ? AND status != 'cancelled'",
+    )
+    .bind(customer_id)
+    .fetch_one(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let total_spent = purchases.iter().map(|item| item.subtotal).sum::<f64>() - refunds;
+
+    Ok(CustomerHistory {
+        customer,
+        order_count: order_ids.len() as i64,
+        total_spent,
+        purchases,
+    })
+}
@@ -4,6 +4,10 @@ use sqlx::SqlitePool;
 use tauri::{Manager, State};
 use tauri_plugin_dialog::{DialogExt, FilePath};
 
+use crate::commands::cost_cmd::compute_valuation;
+use crate::commands::movement_cmd::MOVEMENT_SELECT;
+use crate::commands::product_cmd::PRODUCT_SELECT;
+use crate::error::AppError;
 use crate::models::material::Material;
 use crate::models::movement::Movement;
 use crate::models::product::Product;
@@ -12,7 +16,7 @@ use crate::models::product::Product;
 pub async fn export_database(
     app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let backup_path = app
         .dialog()
         .file()
@@ -25,15 +29,12 @@ pub async fn export_database(
 
     let backup_path = match backup_path {
         Some(FilePath::Path(p)) => p,
-        _ => return Err("No file path selected".to_string()),
+        _ => return Err(AppError::validation("No file path selected")),
     };
 
     // Use VACUUM INTO to create a clean backup copy
     let sql = format!("VACUUM INTO '{}'", backup_path.display());
-    sqlx::query(&sql)
-        .execute(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
+    sqlx::query(&sql).execute(&*pool).await?;
 
     app.dialog()
         .message(format!("{} 資料庫匯出成功!", backup_path.display()))
@@ -47,7 +48,7 @@ pub async fn export_database(
 pub async fn import_database(
     app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let import_path = app
         .dialog()
         .file()
@@ -56,17 +57,21 @@ pub async fn import_database(
 
     let import_path = match import_path {
         Some(FilePath::Path(p)) => p,
-        _ => return Err("No file path selected".to_string()),
+        _ => return Err(AppError::validation("No file path selected")),
     };
 
     pool.close().await;
 
     // Get the app database path
-    let mut db_path = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
     db_path.push("soap.db");
 
     // Copy the imported file to the app database location
-    std::fs::copy(&import_path, &db_path).map_err(|e| format!("Failed to copy database: {}", e))?;
+    std::fs::copy(&import_path, &db_path)
+        .map_err(|e| AppError::Io(format!("Failed to copy database: {}", e)))?;
 
     app.dialog()
         .message(format!(
@@ -83,7 +88,7 @@ pub async fn import_database(
 pub async fn export_database_excel(
     app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let export_file = app
         .dialog()
         .file()
@@ -96,7 +101,7 @@ pub async fn export_database_excel(
 
     let export_path = match export_file {
         Some(FilePath::Path(p)) => p.display().to_string(),
-        _ => return Err("No file path selected".to_string()),
+        _ => return Err(AppError::validation("No file path selected")),
     };
 
     let mut workbook = Workbook::new();
@@ -110,9 +115,12 @@ pub async fn export_database_excel(
     // Export Movements sheet
     export_movements_excel(&mut workbook, &pool).await?;
 
+    // Export Valuation sheet
+    export_valuation_excel(&mut workbook, &pool).await?;
+
     workbook
         .save(&export_path)
-        .map_err(|e| format!("Failed to close Excel file: {}", e))?;
+        .map_err(|e| AppError::Io(format!("Failed to close Excel file: {}", e)))?;
 
     app.dialog()
         .message(format!("資料庫已成功匯出至 Excel 檔案！\n{}", export_path))
@@ -122,16 +130,19 @@ pub async fn export_database_excel(
     Ok(export_path)
 }
 
-async fn export_materials_excel(workbook: &mut Workbook, pool: &SqlitePool) -> Result<(), String> {
+async fn export_materials_excel(
+    workbook: &mut Workbook,
+    pool: &SqlitePool,
+) -> Result<(), AppError> {
     let materials: Vec<Material> = sqlx::query_as("SELECT * FROM materials")
         .fetch_all(pool)
         .await
-        .map_err(|e| format!("Failed to fetch materials: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Failed to fetch materials: {}", e)))?;
 
     let worksheet = workbook
         .add_worksheet()
         .set_name("Material")
-        .map_err(|e| format!("Failed to create Materials sheet: {}", e))?;
+        .map_err(|e| AppError::Io(format!("Failed to create Materials sheet: {}", e)))?;
 
     let header_format = Format::new().set_bold();
     let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
@@ -143,56 +154,74 @@ async fn export_materials_excel(workbook: &mut Workbook, pool: &SqlitePool) -> R
         "單位",
         "目前庫存",
         "低庫存警告",
+        "SAP (NaOH)",
+        "SAP (KOH)",
         "備註",
         "建立時間",
         "刪除時間",
+        "平均成本",
+        "INCI 名稱",
+        "皂化後 INCI 名稱",
+        "SKU",
     ];
 
     for (col, header) in headers.iter().enumerate() {
         worksheet
             .write_with_format(0, col as u16, *header, &header_format)
-            .map_err(|e| format!("Failed to write header: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write header: {}", e)))?;
     }
 
     for (row, material) in materials.iter().enumerate() {
         let row = (row + 1) as u32;
         worksheet
             .write_number(row, 0, material.id as f64)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 1, &material.name)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 2, &material.category)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 3, &material.unit)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_number(row, 4, material.current_stock)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
 
         if let Some(alert) = material.low_stock_alert {
             worksheet
                 .write_number(row, 5, alert)
-                .map_err(|e| format!("Failed to write cell: {}", e))?;
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(sap) = material.sap_naoh {
+            worksheet
+                .write_number(row, 6, sap)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(sap) = material.sap_koh {
+            worksheet
+                .write_number(row, 7, sap)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         }
 
         worksheet
-            .write_string(row, 6, &material.note.clone().unwrap_or_default())
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .write_string(row, 8, &material.note.clone().unwrap_or_default())
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
 
         // Parse and write created_at as datetime
         if let Ok(dt) = DateTime::parse_from_rfc3339(&material.created_at) {
             if let Ok(created_time) = ExcelDateTime::from_timestamp(dt.timestamp()) {
                 worksheet
-                    .write_datetime_with_format(row, 7, created_time, &datetime_format)
-                    .map_err(|e| format!("Failed to write cell: {}", e))?;
+                    .write_datetime_with_format(row, 9, created_time, &datetime_format)
+                    .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
             };
         } else {
             worksheet
-                .write_string(row, 7, &material.created_at)
-                .map_err(|e| format!("Failed to write cell: {}", e))?;
+                .write_string(row, 9, &material.created_at)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         }
 
         // Write deleted_at as datetime if exists
@@ -200,46 +229,70 @@ async fn export_materials_excel(workbook: &mut Workbook, pool: &SqlitePool) -> R
             if let Ok(dt) = DateTime::parse_from_rfc3339(deleted) {
                 if let Ok(excel_dt) = ExcelDateTime::from_timestamp(dt.timestamp()) {
                     worksheet
-                        .write_datetime_with_format(row, 8, excel_dt, &datetime_format)
-                        .map_err(|e| format!("Failed to write cell: {}", e))?;
+                        .write_datetime_with_format(row, 10, excel_dt, &datetime_format)
+                        .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
                 };
             } else {
                 worksheet
-                    .write_string(row, 8, deleted)
-                    .map_err(|e| format!("Failed to write cell: {}", e))?;
+                    .write_string(row, 10, deleted)
+                    .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
             }
         }
+
+        if let Some(avg_cost) = material.avg_cost {
+            worksheet
+                .write_number(row, 11, avg_cost)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(inci_name) = &material.inci_name {
+            worksheet
+                .write_string(row, 12, inci_name)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(saponified_inci_name) = &material.saponified_inci_name {
+            worksheet
+                .write_string(row, 13, saponified_inci_name)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(sku) = &material.sku {
+            worksheet
+                .write_string(row, 14, sku)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
     }
 
     worksheet
         .set_column_width(1, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
         .set_column_width(2, 10)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
         .set_column_width(5, 10)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
-        .set_column_width(7, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .set_column_width(9, 20)
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
-        .set_column_width(8, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .set_column_width(10, 20)
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
 
     Ok(())
 }
 
-async fn export_products_excel(workbook: &mut Workbook, pool: &SqlitePool) -> Result<(), String> {
-    let products: Vec<Product> = sqlx::query_as("SELECT * FROM products")
+async fn export_products_excel(workbook: &mut Workbook, pool: &SqlitePool) -> Result<(), AppError> {
+    let products: Vec<Product> = sqlx::query_as(PRODUCT_SELECT)
         .fetch_all(pool)
         .await
-        .map_err(|e| format!("Failed to fetch products: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Failed to fetch products: {}", e)))?;
 
     let worksheet = workbook
         .add_worksheet()
         .set_name("Products")
-        .map_err(|e| format!("Failed to create Products sheet: {}", e))?;
+        .map_err(|e| AppError::Io(format!("Failed to create Products sheet: {}", e)))?;
 
     let header_format = Format::new().set_bold();
     let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
@@ -250,49 +303,60 @@ async fn export_products_excel(workbook: &mut Workbook, pool: &SqlitePool) -> Re
         "分類",
         "單位",
         "目前庫存",
+        "熟成中",
+        "可售",
         "備註",
         "建立時間",
         "刪除時間",
+        "零售價",
+        "批發價",
+        "SKU",
     ];
 
     for (col, header) in headers.iter().enumerate() {
         worksheet
             .write_with_format(0, col as u16, *header, &header_format)
-            .map_err(|e| format!("Failed to write header: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write header: {}", e)))?;
     }
 
     for (row, product) in products.iter().enumerate() {
         let row = (row + 1) as u32;
         worksheet
             .write_number(row, 0, product.id as f64)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 1, &product.name)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 2, &product.category)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 3, &product.unit)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_number(row, 4, product.current_stock as f64)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_number(row, 5, product.curing_stock as f64)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_number(row, 6, product.available_stock as f64)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
-            .write_string(row, 5, &product.note.clone().unwrap_or_default())
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .write_string(row, 7, &product.note.clone().unwrap_or_default())
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
 
         // Write created_at as datetime
         if let Ok(dt) = DateTime::parse_from_rfc3339(&product.created_at) {
             if let Ok(created_time) = ExcelDateTime::from_timestamp(dt.timestamp()) {
                 worksheet
-                    .write_datetime_with_format(row, 6, created_time, &datetime_format)
-                    .map_err(|e| format!("Failed to write cell: {}", e))?;
+                    .write_datetime_with_format(row, 8, created_time, &datetime_format)
+                    .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
             };
         } else {
             worksheet
-                .write_string(row, 6, &product.created_at)
-                .map_err(|e| format!("Failed to write cell: {}", e))?;
+                .write_string(row, 8, &product.created_at)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         }
 
         // Write deleted_at as datetime if exists
@@ -300,53 +364,64 @@ async fn export_products_excel(workbook: &mut Workbook, pool: &SqlitePool) -> Re
             if let Ok(dt) = DateTime::parse_from_rfc3339(deleted) {
                 if let Ok(deleted_time) = ExcelDateTime::from_timestamp(dt.timestamp()) {
                     worksheet
-                        .write_datetime(row, 7, deleted_time)
-                        .map_err(|e| format!("Failed to write cell: {}", e))?;
+                        .write_datetime(row, 9, deleted_time)
+                        .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
                 };
             } else {
                 worksheet
-                    .write_string(row, 7, deleted)
-                    .map_err(|e| format!("Failed to write cell: {}", e))?;
+                    .write_string(row, 9, deleted)
+                    .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
             }
         }
+
+        if let Some(price) = product.retail_price {
+            worksheet
+                .write_number(row, 10, price)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(price) = product.wholesale_price {
+            worksheet
+                .write_number(row, 11, price)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(sku) = &product.sku {
+            worksheet
+                .write_string(row, 12, sku)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
     }
 
     worksheet
         .set_column_width(1, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
         .set_column_width(2, 10)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
-        .set_column_width(6, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .set_column_width(8, 20)
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
-        .set_column_width(7, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .set_column_width(9, 20)
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
 
     Ok(())
 }
 
-async fn export_movements_excel(workbook: &mut Workbook, pool: &SqlitePool) -> Result<(), String> {
-    let movements: Vec<Movement> = sqlx::query_as(
-        "
-        SELECT 
-            il.*,
-            COALESCE(m.name, p.name) as item_name,
-            COALESCE(m.unit, p.unit) as item_unit,
-            COALESCE(m.category, p.category) as item_category
-        FROM inventory_logs il
-        LEFT JOIN materials m ON il.item_type = 'material' AND il.item_id = m.id
-        LEFT JOIN products p ON il.item_type = 'product' AND il.item_id = p.id",
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch movements: {}", e))?;
+async fn export_movements_excel(
+    workbook: &mut Workbook,
+    pool: &SqlitePool,
+) -> Result<(), AppError> {
+    let movements: Vec<Movement> = sqlx::query_as(MOVEMENT_SELECT)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch movements: {}", e)))?;
 
     let worksheet = workbook
         .add_worksheet()
         .set_name("Movements")
-        .map_err(|e| format!("Failed to create Movements sheet: {}", e))?;
+        .map_err(|e| AppError::Io(format!("Failed to create Movements sheet: {}", e)))?;
 
     let header_format = Format::new().set_bold();
     let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
@@ -363,70 +438,218 @@ async fn export_movements_excel(workbook: &mut Workbook, pool: &SqlitePool) -> R
         "操作類型",
         "備註",
         "建立時間",
+        "生產批號",
+        "單位成本",
+        "採購單",
+        "銷售單",
+        "沖銷異動",
+        "作廢時間",
+        "校準目標",
     ];
 
     for (col, header) in headers.iter().enumerate() {
         worksheet
             .write_with_format(0, col as u16, *header, &header_format)
-            .map_err(|e| format!("Failed to write header: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write header: {}", e)))?;
     }
 
     for (row, movement) in movements.iter().enumerate() {
         let row = (row + 1) as u32;
         worksheet
             .write_number(row, 0, movement.id as f64)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_number(row, 1, movement.item_id as f64)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
-            .write_string(row, 2, &movement.item_type)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .write_string(row, 2, movement.item_type.as_str())
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 3, &movement.item_name)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 4, &movement.item_unit)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_number(row, 5, movement.change_amount)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_number(row, 6, movement.old_stock)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_number(row, 7, movement.new_stock)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
-            .write_string(row, 8, &movement.action_type)
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .write_string(row, 8, movement.action_type.as_str())
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         worksheet
             .write_string(row, 9, &movement.note.clone().unwrap_or_default())
-            .map_err(|e| format!("Failed to write cell: {}", e))?;
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
 
         // Write created_at as datetime
         if let Ok(dt) = DateTime::parse_from_rfc3339(&movement.created_at) {
             if let Ok(created_time) = ExcelDateTime::from_timestamp(dt.timestamp()) {
                 worksheet
                     .write_datetime_with_format(row, 10, created_time, &datetime_format)
-                    .map_err(|e| format!("Failed to write cell: {}", e))?;
+                    .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
             };
         } else {
             worksheet
                 .write_string(row, 10, &movement.created_at)
-                .map_err(|e| format!("Failed to write cell: {}", e))?;
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(lot_code) = &movement.related_batch {
+            worksheet
+                .write_string(row, 11, lot_code)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(unit_cost) = movement.unit_cost {
+            worksheet
+                .write_number(row, 12, unit_cost)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(purchase_order_id) = movement.purchase_order_id {
+            worksheet
+                .write_number(row, 13, purchase_order_id as f64)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(sales_order_id) = movement.sales_order_id {
+            worksheet
+                .write_number(row, 14, sales_order_id as f64)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(reversal_of) = movement.reversal_of {
+            worksheet
+                .write_number(row, 15, reversal_of as f64)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        if let Some(voided_at) = &movement.voided_at {
+            if let Ok(dt) = DateTime::parse_from_rfc3339(voided_at) {
+                if let Ok(excel_dt) = ExcelDateTime::from_timestamp(dt.timestamp()) {
+                    worksheet
+                        .write_datetime_with_format(row, 16, excel_dt, &datetime_format)
+                        .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+                };
+            } else {
+                worksheet
+                    .write_string(row, 16, voided_at)
+                    .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+            }
+        }
+
+        if let Some(target_stock) = movement.target_stock {
+            worksheet
+                .write_number(row, 17, target_stock)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
         }
     }
 
     worksheet
         .set_column_width(3, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
         .set_column_width(9, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
     worksheet
         .set_column_width(10, 20)
-        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
+
+    Ok(())
+}
+
+async fn export_valuation_excel(
+    workbook: &mut Workbook,
+    pool: &SqlitePool,
+) -> Result<(), AppError> {
+    let valuation = compute_valuation(pool).await.map_err(AppError::Database)?;
+
+    let worksheet = workbook
+        .add_worksheet()
+        .set_name("Valuation")
+        .map_err(|e| AppError::Io(format!("Failed to create Valuation sheet: {}", e)))?;
+
+    let header_format = Format::new().set_bold();
+
+    let headers = [
+        "原料ID",
+        "名稱",
+        "分類",
+        "單位",
+        "數量",
+        "平均成本",
+        "庫存價值",
+    ];
+
+    for (col, header) in headers.iter().enumerate() {
+        worksheet
+            .write_with_format(0, col as u16, *header, &header_format)
+            .map_err(|e| AppError::Io(format!("Failed to write header: {}", e)))?;
+    }
+
+    let mut row = 1;
+    for material in &valuation.materials {
+        worksheet
+            .write_number(row, 0, material.material_id as f64)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_string(row, 1, &material.material_name)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_string(row, 2, &material.category)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_string(row, 3, &material.unit)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_number(row, 4, material.quantity)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+
+        if let Some(avg_cost) = material.avg_cost {
+            worksheet
+                .write_number(row, 5, avg_cost)
+                .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        }
+
+        worksheet
+            .write_number(row, 6, material.value)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        row += 1;
+    }
+
+    // Category subtotals and the grand total below the material rows
+    row += 1;
+    for category in &valuation.categories {
+        worksheet
+            .write_string(row, 2, &category.category)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_string(row, 5, "小計")
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        worksheet
+            .write_number(row, 6, category.value)
+            .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+        row += 1;
+    }
+
+    worksheet
+        .write_with_format(row, 5, "總計", &header_format)
+        .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+    worksheet
+        .write_number_with_format(row, 6, valuation.total_value, &header_format)
+        .map_err(|e| AppError::Io(format!("Failed to write cell: {}", e)))?;
+
+    worksheet
+        .set_column_width(1, 20)
+        .map_err(|e| AppError::Io(format!("Failed to set column 1 width {e}")))?;
+    worksheet
+        .set_column_width(6, 15)
+        .map_err(|e| AppError::Io(format!("Failed to set column 6 width {e}")))?;
 
     Ok(())
 }
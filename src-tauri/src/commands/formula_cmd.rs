@@ -0,0 +1,293 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+
+use crate::commands::recipe_cmd::fetch_recipe;
+use crate::models::formula::{
+    CalculateLyeRequest, FattyAcidProfile, LyeResult, LyeType, OilLye, OilWeight, QualityScore,
+    SoapQuality, SoapQualityRequest,
+};
+use crate::models::recipe::RecipeDetail;
+
+/// Material category whose recipe lines are treated as saponifiable oils
+pub(crate) const OIL_CATEGORY: &str = "油品";
+
+/// Molar mass ratio KOH / NaOH, used to derive a KOH SAP value from a NaOH one
+const KOH_PER_NAOH: f64 = 56.106 / 39.997;
+
+#[tauri::command]
+pub async fn calculate_lye(
+    pool: State<'_, SqlitePool>,
+    req: CalculateLyeRequest,
+) -> Result<LyeResult, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    let oils = select_oils(&mut conn, req.recipe_id, req.oils).await?;
+
+    compute_lye(
+        &mut conn,
+        &oils,
+        req.lye_type,
+        req.superfat,
+        req.water_ratio,
+        req.lye_concentration,
+    )
+    .await
+}
+
+/// Computes the standard soap quality numbers for a recipe's oil blend or ad-hoc oils
+#[tauri::command]
+pub async fn calculate_soap_quality(
+    pool: State<'_, SqlitePool>,
+    req: SoapQualityRequest,
+) -> Result<SoapQuality, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    let oils = select_oils(&mut conn, req.recipe_id, req.oils).await?;
+    if oils.is_empty() {
+        return Err("沒有可計算的油品".to_string());
+    }
+
+    let total_weight: f64 = oils.iter().map(|oil| oil.weight).sum();
+    if oils.iter().any(|oil| oil.weight <= 0.0) {
+        return Err("油品重量必須大於 0".to_string());
+    }
+
+    // Weighted average of every oil's fatty acids and KOH saponification value
+    let mut blend = FattyAcidProfile::default();
+    let mut sap_koh = 0.0;
+    for oil in &oils {
+        let (name, sap_naoh, oil_sap_koh): (String, Option<f64>, Option<f64>) =
+            sqlx::query_as("SELECT name, sap_naoh, sap_koh FROM materials WHERE id = ?")
+                .bind(oil.material_id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("查無原料 id {}", oil.material_id))?;
+
+        let profile = sqlx::query_as::<_, FattyAcidProfile>(
+            "SELECT lauric, myristic, palmitic, stearic, ricinoleic, oleic, linoleic, linolenic
+             FROM material_fatty_acids
+             WHERE material_id = ?",
+        )
+        .bind(oil.material_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("油品「{}」尚未設定脂肪酸組成", name))?;
+
+        let oil_sap_koh = oil_sap_koh
+            .or(sap_naoh.map(|sap| sap * KOH_PER_NAOH))
+            .ok_or_else(|| format!("油品「{}」尚未設定 SAP 值", name))?;
+
+        let share = oil.weight / total_weight;
+        blend.lauric += profile.lauric * share;
+        blend.myristic += profile.myristic * share;
+        blend.palmitic += profile.palmitic * share;
+        blend.stearic += profile.stearic * share;
+        blend.ricinoleic += profile.ricinoleic * share;
+        blend.oleic += profile.oleic * share;
+        blend.linoleic += profile.linoleic * share;
+        blend.linolenic += profile.linolenic * share;
+        sap_koh += oil_sap_koh * share;
+    }
+
+    let iodine = blend.oleic * 0.860
+        + blend.linoleic * 1.732
+        + blend.linolenic * 2.616
+        + blend.ricinoleic * 0.815;
+    // INS uses the KOH SAP value in mg/g
+    let ins = sap_koh * 1000.0 - iodine;
+
+    Ok(SoapQuality {
+        hardness: score(
+            blend.lauric + blend.myristic + blend.palmitic + blend.stearic,
+            29.0,
+            54.0,
+        ),
+        cleansing: score(blend.lauric + blend.myristic, 12.0, 22.0),
+        conditioning: score(
+            blend.oleic + blend.linoleic + blend.linolenic + blend.ricinoleic,
+            44.0,
+            69.0,
+        ),
+        bubbly: score(blend.lauric + blend.myristic + blend.ricinoleic, 14.0, 46.0),
+        creamy: score(
+            blend.palmitic + blend.stearic + blend.ricinoleic,
+            16.0,
+            48.0,
+        ),
+        iodine: score(iodine, 41.0, 70.0),
+        ins: score(ins, 136.0, 165.0),
+        fatty_acids: blend,
+    })
+}
+
+/// Calculates the lye a recipe needs for one batch using its stored lye settings.
+/// Returns `None` when the recipe has no lye material configured.
+pub(crate) async fn recipe_lye(
+    conn: &mut SqliteConnection,
+    recipe: &RecipeDetail,
+) -> Result<Option<(i64, LyeResult)>, String> {
+    let lye = &recipe.recipe.lye;
+    let Some(material_id) = lye.lye_material_id else {
+        return Ok(None);
+    };
+
+    let lye_type = lye
+        .lye_type
+        .ok_or_else(|| format!("配方「{}」未設定鹼的種類", recipe.recipe.name))?;
+
+    let result = compute_lye(
+        conn,
+        &recipe_oils(recipe)?,
+        lye_type,
+        lye.superfat.unwrap_or(0.0),
+        lye.water_ratio,
+        lye.lye_concentration,
+    )
+    .await?;
+
+    Ok(Some((material_id, result)))
+}
+
+async fn select_oils(
+    conn: &mut SqliteConnection,
+    recipe_id: Option<i64>,
+    oils: Option<Vec<OilWeight>>,
+) -> Result<Vec<OilWeight>, String> {
+    match (recipe_id, oils) {
+        (Some(recipe_id), None) => {
+            let recipe = fetch_recipe(conn, recipe_id).await?;
+            recipe_oils(&recipe)
+        }
+        (None, Some(oils)) => Ok(oils),
+        _ => Err("請指定配方或油品重量其中之一".to_string()),
+    }
+}
+
+fn score(value: f64, min: f64, max: f64) -> QualityScore {
+    QualityScore {
+        value,
+        min,
+        max,
+        in_range: (min..=max).contains(&value),
+    }
+}
+
+/// Oil weights of one batch of a recipe, taken from its oil-category lines
+pub(crate) fn recipe_oils(recipe: &RecipeDetail) -> Result<Vec<OilWeight>, String> {
+    recipe
+        .lines
+        .iter()
+        .filter(|line| line.material_category == OIL_CATEGORY)
+        .map(|line| {
+            let weight = weight_in_grams(line.quantity, &line.material_unit, line.material_density)
+                .ok_or_else(|| {
+                    format!(
+                        "油品「{}」以 {} 記錄，請設定密度 (g/ml) 或改用重量單位 (g / kg)",
+                        line.material_name, line.material_unit
+                    )
+                })?;
+
+            Ok(OilWeight {
+                material_id: line.material_id,
+                weight,
+            })
+        })
+        .collect()
+}
+
+/// Converts a quantity to grams. Volume units need the material's density
+/// (g/ml); returns `None` without one, and for count units.
+pub(crate) fn weight_in_grams(quantity: f64, unit: &str, density: Option<f64>) -> Option<f64> {
+    grams_per_unit(unit, density).map(|grams| quantity * grams)
+}
+
+/// Converts a weight in grams to a quantity in `unit`, the inverse of `weight_in_grams`
+pub(crate) fn quantity_from_grams(grams: f64, unit: &str, density: Option<f64>) -> Option<f64> {
+    grams_per_unit(unit, density).map(|per_unit| grams / per_unit)
+}
+
+fn grams_per_unit(unit: &str, density: Option<f64>) -> Option<f64> {
+    let density = density.filter(|density| *density > 0.0);
+    match unit.to_lowercase().as_str() {
+        "g" => Some(1.0),
+        "kg" => Some(1000.0),
+        "ml" => density,
+        "l" => density.map(|density| density * 1000.0),
+        _ => None,
+    }
+}
+
+async fn compute_lye(
+    conn: &mut SqliteConnection,
+    oils: &[OilWeight],
+    lye_type: LyeType,
+    superfat: f64,
+    water_ratio: Option<f64>,
+    lye_concentration: Option<f64>,
+) -> Result<LyeResult, String> {
+    if oils.is_empty() {
+        return Err("沒有可計算的油品".to_string());
+    }
+    if !(0.0..100.0).contains(&superfat) {
+        return Err("超脂比例必須介於 0 到 100 之間".to_string());
+    }
+
+    let mut oil_lyes = Vec::with_capacity(oils.len());
+    for oil in oils {
+        if oil.weight <= 0.0 {
+            return Err(format!("原料 id {} 的重量必須大於 0", oil.material_id));
+        }
+
+        let (material_name, sap_naoh, sap_koh): (String, Option<f64>, Option<f64>) =
+            sqlx::query_as("SELECT name, sap_naoh, sap_koh FROM materials WHERE id = ?")
+                .bind(oil.material_id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("查無原料 id {}", oil.material_id))?;
+
+        let sap = match lye_type {
+            LyeType::Naoh => sap_naoh,
+            LyeType::Koh => sap_koh,
+        }
+        .ok_or_else(|| format!("油品「{}」尚未設定 SAP 值", material_name))?;
+
+        oil_lyes.push(OilLye {
+            material_id: oil.material_id,
+            material_name,
+            weight: oil.weight,
+            sap,
+            lye_weight: oil.weight * sap,
+        });
+    }
+
+    let oil_weight: f64 = oil_lyes.iter().map(|oil| oil.weight).sum();
+    let full_lye: f64 = oil_lyes.iter().map(|oil| oil.lye_weight).sum();
+    let lye_weight = full_lye * (1.0 - superfat / 100.0);
+
+    let water_ratio = match (water_ratio, lye_concentration) {
+        (Some(ratio), None) if ratio > 0.0 => ratio,
+        (None, Some(concentration)) if concentration > 0.0 && concentration < 100.0 => {
+            (100.0 - concentration) / concentration
+        }
+        _ => {
+            return Err(
+                "請指定有效的水鹼比或鹼液濃度其中之一 (濃度需介於 0 到 100 之間)".to_string(),
+            )
+        }
+    };
+    let water_weight = lye_weight * water_ratio;
+
+    Ok(LyeResult {
+        lye_type,
+        superfat,
+        oil_weight,
+        lye_weight,
+        water_weight,
+        water_ratio,
+        lye_concentration: lye_weight / (lye_weight + water_weight) * 100.0,
+        oils: oil_lyes,
+    })
+}
@@ -0,0 +1,273 @@
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::State;
+use tauri_plugin_dialog::{DialogExt, FilePath};
+
+use crate::commands::formula_cmd::{weight_in_grams, OIL_CATEGORY};
+use crate::commands::production_cmd::fetch_batch;
+use crate::commands::settings_cmd::{
+    read_integer_setting, read_setting, SHELF_LIFE_MONTHS, WORKSHOP_ADDRESS, WORKSHOP_NAME,
+    WORKSHOP_PHONE,
+};
+use crate::models::label::{BatchLabel, LabelIngredient};
+use crate::pdf::label;
+
+const DEFAULT_SHELF_LIFE_MONTHS: i64 = 24;
+/// Labels printed when no count is given: one full sheet
+const DEFAULT_LABEL_COPIES: i64 = label::LABELS_PER_PAGE as i64;
+const MAX_LABEL_COPIES: i64 = 1000;
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// A material consumed by a batch, totalled over its movements
+#[derive(FromRow)]
+struct MaterialUsage {
+    material_id: i64,
+    name: String,
+    category: String,
+    inci_name: Option<String>,
+    saponified_inci_name: Option<String>,
+    unit: String,
+    density: Option<f64>,
+    /// In the material's unit
+    quantity: f64,
+}
+
+/// Collects the label contents of a production batch, for previewing before printing
+#[tauri::command]
+pub async fn get_batch_label(
+    pool: State<'_, SqlitePool>,
+    batch_id: i64,
+) -> Result<BatchLabel, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    build_label(&mut conn, batch_id).await
+}
+
+/// Renders a sheet of cosmetic labels for a production batch and saves it as a PDF
+#[tauri::command]
+pub async fn generate_label(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    batch_id: i64,
+    copies: Option<i64>,
+) -> Result<String, String> {
+    let copies = label_copies(copies)?;
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let batch_label = build_label(&mut conn, batch_id).await?;
+    drop(conn);
+
+    let export_file = app
+        .dialog()
+        .file()
+        .add_filter("PDF", &["pdf"])
+        .set_file_name(format!("label_{}.pdf", batch_label.lot_code))
+        .blocking_save_file();
+
+    let export_path = match export_file {
+        Some(FilePath::Path(p)) => p.display().to_string(),
+        _ => return Err("No file path selected".to_string()),
+    };
+
+    let pdf = label::render(&batch_label, copies);
+    std::fs::write(&export_path, pdf).map_err(|e| format!("Failed to write PDF file: {}", e))?;
+
+    app.dialog()
+        .message(format!("標籤已儲存！\n{}", export_path))
+        .title("匯出成功")
+        .blocking_show();
+
+    Ok(export_path)
+}
+
+/// Renders the label sheets of a production batch as SVG, one per A4 sheet,
+/// for the frontend to rasterise and pass to `save_label_png`
+#[tauri::command]
+pub async fn get_label_sheets(
+    pool: State<'_, SqlitePool>,
+    batch_id: i64,
+    copies: Option<i64>,
+) -> Result<Vec<String>, String> {
+    let copies = label_copies(copies)?;
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let batch_label = build_label(&mut conn, batch_id).await?;
+
+    Ok(label::render_svg(&batch_label, copies))
+}
+
+/// Saves rasterised label sheets as PNG files where the user picks. Sheets
+/// after the first are saved next to it with a page number suffix.
+#[tauri::command]
+pub async fn save_label_png(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    batch_id: i64,
+    sheets: Vec<Vec<u8>>,
+) -> Result<String, String> {
+    if sheets.is_empty() {
+        return Err("沒有可儲存的標籤".to_string());
+    }
+    if sheets.iter().any(|sheet| !sheet.starts_with(PNG_SIGNATURE)) {
+        return Err("標籤圖檔格式錯誤".to_string());
+    }
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let batch = fetch_batch(&mut conn, batch_id).await?;
+    drop(conn);
+    let lot_code = batch.lot_code.unwrap_or_else(|| format!("B{}", batch_id));
+
+    let export_file = app
+        .dialog()
+        .file()
+        .add_filter("PNG", &["png"])
+        .set_file_name(format!("label_{}.png", lot_code))
+        .blocking_save_file();
+
+    let export_path = match export_file {
+        Some(FilePath::Path(p)) => p,
+        _ => return Err("No file path selected".to_string()),
+    };
+
+    for (index, sheet) in sheets.iter().enumerate() {
+        let path = if index == 0 {
+            export_path.clone()
+        } else {
+            let stem = export_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| format!("label_{}", lot_code));
+            export_path.with_file_name(format!("{}_{}.png", stem, index + 1))
+        };
+        std::fs::write(&path, sheet).map_err(|e| format!("Failed to write PNG file: {}", e))?;
+    }
+
+    let export_path = export_path.display().to_string();
+    app.dialog()
+        .message(format!("標籤已儲存！\n{}", export_path))
+        .title("匯出成功")
+        .blocking_show();
+
+    Ok(export_path)
+}
+
+fn label_copies(copies: Option<i64>) -> Result<usize, String> {
+    let copies = copies.unwrap_or(DEFAULT_LABEL_COPIES);
+    if !(1..=MAX_LABEL_COPIES).contains(&copies) {
+        return Err(format!("標籤數量必須介於 1 到 {} 之間", MAX_LABEL_COPIES));
+    }
+    Ok(copies as usize)
+}
+
+/// Builds the ingredient list from the materials the batch actually consumed,
+/// weighed in grams so materials kept in different units sort together.
+/// Oils of a lye recipe are listed as their saponified salts and the lye itself
+/// is left out, since it is used up by the reaction.
+async fn build_label(conn: &mut SqliteConnection, batch_id: i64) -> Result<BatchLabel, String> {
+    let batch = fetch_batch(conn, batch_id).await?;
+
+    let lye_material_id: Option<i64> = match batch.recipe_id {
+        Some(recipe_id) => sqlx::query_scalar("SELECT lye_material_id FROM recipes WHERE id = ?")
+            .bind(recipe_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?
+            .flatten(),
+        None => None,
+    };
+
+    let usages = sqlx::query_as::<_, MaterialUsage>(
+        "SELECT m.id as material_id, m.name, COALESCE(m.category, '') as category, m.inci_name,
+                m.saponified_inci_name, m.unit, m.density,
+                SUM(il.change_amount) as quantity
+         FROM inventory_logs il
+         JOIN materials m ON il.item_id = m.id
+         WHERE il.batch_id = ? AND il.item_type = 'material' AND il.action_type = 'out'
+         GROUP BY m.id",
+    )
+    .bind(batch_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if usages.is_empty() {
+        return Err(format!("生產批次 {} 沒有原料用量紀錄", batch_id));
+    }
+
+    let mut ingredients: Vec<LabelIngredient> = Vec::new();
+    let mut missing_inci = Vec::new();
+    for MaterialUsage {
+        material_id,
+        name,
+        category,
+        inci_name,
+        saponified_inci_name,
+        unit,
+        density,
+        quantity,
+    } in usages
+    {
+        if Some(material_id) == lye_material_id {
+            continue;
+        }
+        let weight = weight_in_grams(quantity, &unit, density).ok_or_else(|| {
+            format!(
+                "原料「{}」以 {} 記錄，無法換算重量排序成分，請設定密度 (g/ml) 或改用重量單位",
+                name, unit
+            )
+        })?;
+
+        let inci = if lye_material_id.is_some() && category == OIL_CATEGORY {
+            saponified_inci_name
+        } else {
+            inci_name
+        };
+        let inci = match inci.filter(|inci| !inci.trim().is_empty()) {
+            Some(inci) => inci.trim().to_string(),
+            None => {
+                missing_inci.push(name.clone());
+                name
+            }
+        };
+
+        match ingredients
+            .iter_mut()
+            .find(|ingredient| ingredient.name == inci)
+        {
+            Some(ingredient) => ingredient.weight += weight,
+            None => ingredients.push(LabelIngredient { name: inci, weight }),
+        }
+    }
+    ingredients.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    let manufactured = parse_date(&batch.produced_at)
+        .ok_or_else(|| format!("生產批次 {} 的生產日期格式錯誤", batch_id))?;
+    let shelf_life = read_integer_setting(conn, SHELF_LIFE_MONTHS)
+        .await?
+        .unwrap_or(DEFAULT_SHELF_LIFE_MONTHS);
+    let expires = u32::try_from(shelf_life)
+        .ok()
+        .and_then(|months| manufactured.checked_add_months(chrono::Months::new(months)))
+        .ok_or_else(|| format!("設定 {} 的值無效: {}", SHELF_LIFE_MONTHS, shelf_life))?;
+
+    Ok(BatchLabel {
+        batch_id,
+        product_id: batch.product_id,
+        product_name: batch.product_name,
+        lot_code: batch.lot_code.unwrap_or_else(|| format!("B{}", batch_id)),
+        manufactured_on: manufactured.format("%Y-%m-%d").to_string(),
+        expires_on: expires.format("%Y-%m-%d").to_string(),
+        manufacturer: read_setting(conn, WORKSHOP_NAME).await?,
+        manufacturer_address: read_setting(conn, WORKSHOP_ADDRESS).await?,
+        manufacturer_phone: read_setting(conn, WORKSHOP_PHONE).await?,
+        ingredients,
+        missing_inci,
+    })
+}
+
+/// Local date of an RFC 3339 timestamp, or a plain YYYY-MM-DD date
+fn parse_date(value: &str) -> Option<chrono::NaiveDate> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&chrono::Local).date_naive())
+        .ok()
+        .or_else(|| chrono::NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok())
+}
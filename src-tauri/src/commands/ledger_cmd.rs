@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use sqlx::{FromRow, SqliteConnection, SqlitePool, Transaction};
+use tauri::State;
+
+use crate::commands::movement_cmd::insert_inventory_log;
+use crate::models::ledger::{LedgerIssue, LedgerIssueKind, LedgerReport};
+use crate::models::movement::{ActionType, CreateMovementRequest, ItemType, MovementLink};
+
+/// Differences smaller than this are rounding noise in REAL arithmetic
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(FromRow)]
+struct StockBalance {
+    item_type: ItemType,
+    item_id: i64,
+    name: String,
+    current_stock: f64,
+}
+
+#[derive(FromRow)]
+struct LedgerRow {
+    id: i64,
+    item_type: ItemType,
+    item_id: i64,
+    action_type: ActionType,
+    change_amount: f64,
+    old_stock: f64,
+    new_stock: f64,
+}
+
+/// Replays every material's and product's inventory logs in the order they were
+/// written and reports where the ledger and the stored balances disagree.
+/// Each item's history opens at zero, so stock held before its first log row
+/// shows up as a gap, and an item never logged that holds stock shows up as a
+/// balance mismatch. Every row's `new_stock` must also follow from its
+/// `old_stock` and `change_amount`.
+/// With `repair`, each balance that differs from its last logged stock gets a
+/// corrective `adj` entry bridging the two, keeping the stored balance; for an
+/// unlogged item that entry is its opening balance. Other issues are only
+/// reported.
+#[tauri::command]
+pub async fn verify_stock_ledger(
+    pool: State<'_, SqlitePool>,
+    repair: Option<bool>,
+) -> Result<LedgerReport, String> {
+    let repair = repair.unwrap_or(false);
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+    let report = check_stock_ledger(&mut tx, repair).await?;
+    if repair {
+        tx.commit().await.map_err(|e| e.to_string())?;
+    }
+    Ok(report)
+}
+
+pub(crate) async fn check_stock_ledger(
+    conn: &mut SqliteConnection,
+    repair: bool,
+) -> Result<LedgerReport, String> {
+    let balances = sqlx::query_as::<_, StockBalance>(
+        "SELECT 'material' as item_type, id as item_id, name, current_stock FROM materials
+         UNION ALL
+         SELECT 'product', id, name, CAST(current_stock AS REAL) FROM products
+         ORDER BY item_type, item_id",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let rows = sqlx::query_as::<_, LedgerRow>(
+        "SELECT id, item_type, item_id, action_type, change_amount, old_stock, new_stock
+         FROM inventory_logs
+         ORDER BY item_type, item_id, id",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let names: HashMap<(ItemType, i64), &str> = balances
+        .iter()
+        .map(|b| ((b.item_type, b.item_id), b.name.as_str()))
+        .collect();
+    let item_name = |item_type: ItemType, item_id: i64| {
+        names
+            .get(&(item_type, item_id))
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("#{}", item_id))
+    };
+
+    // Stock after each item's latest log row
+    let mut ledger: HashMap<(ItemType, i64), f64> = HashMap::new();
+    let mut issues = Vec::new();
+    for row in &rows {
+        let key = (row.item_type, row.item_id);
+        let previous = ledger.get(&key).copied().unwrap_or(0.0);
+        if (row.old_stock - previous).abs() > TOLERANCE {
+            issues.push(LedgerIssue {
+                kind: LedgerIssueKind::Gap,
+                item_type: row.item_type,
+                item_id: row.item_id,
+                item_name: item_name(row.item_type, row.item_id),
+                inventory_log_id: Some(row.id),
+                expected: previous,
+                actual: row.old_stock,
+            });
+        }
+
+        // Adjustments log the signed delta, everything else a positive quantity
+        let moved = match row.action_type {
+            ActionType::In | ActionType::Production | ActionType::Return | ActionType::Adj => {
+                row.old_stock + row.change_amount
+            }
+            ActionType::Out | ActionType::Sale | ActionType::Waste | ActionType::Quarantine => {
+                row.old_stock - row.change_amount
+            }
+        };
+        if (row.new_stock - moved).abs() > TOLERANCE {
+            issues.push(LedgerIssue {
+                kind: LedgerIssueKind::RowMismatch,
+                item_type: row.item_type,
+                item_id: row.item_id,
+                item_name: item_name(row.item_type, row.item_id),
+                inventory_log_id: Some(row.id),
+                expected: moved,
+                actual: row.new_stock,
+            });
+        }
+        ledger.insert(key, row.new_stock);
+    }
+
+    let mut corrections = Vec::new();
+    for balance in &balances {
+        let expected = ledger
+            .get(&(balance.item_type, balance.item_id))
+            .copied()
+            .unwrap_or(0.0);
+        if (balance.current_stock - expected).abs() <= TOLERANCE {
+            continue;
+        }
+
+        issues.push(LedgerIssue {
+            kind: LedgerIssueKind::BalanceMismatch,
+            item_type: balance.item_type,
+            item_id: balance.item_id,
+            item_name: balance.name.clone(),
+            inventory_log_id: None,
+            expected,
+            actual: balance.current_stock,
+        });
+
+        if repair {
+            let req = CreateMovementRequest {
+                note: Some("帳務校正".to_string()),
+                ..CreateMovementRequest::new(
+                    balance.item_type,
+                    balance.item_id,
+                    ActionType::Adj,
+                    balance.current_stock,
+                )
+            };
+            let log_id = insert_inventory_log(
+                &mut *conn,
+                &req,
+                expected,
+                balance.current_stock,
+                MovementLink::default(),
+            )
+            .await?;
+            corrections.push(log_id);
+        }
+    }
+
+    Ok(LedgerReport {
+        items_checked: balances.len() as i64,
+        logs_checked: rows.len() as i64,
+        issues,
+        corrections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::db::migrate::migrate;
+
+    async fn seeded() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        migrate(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn row_that_does_not_follow_from_its_change_is_reported() {
+        let pool = seeded().await;
+        let mut conn = pool.acquire().await.unwrap();
+        assert!(check_stock_ledger(&mut conn, false)
+            .await
+            .unwrap()
+            .issues
+            .is_empty());
+
+        // An `out` of 10 that only took 5 off, with the balance following the row
+        let old_stock: f64 = sqlx::query_scalar("SELECT current_stock FROM materials WHERE id = 1")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        let log_id: i64 = sqlx::query_scalar(
+            "INSERT INTO inventory_logs (item_id, item_type, action_type, change_amount, old_stock, new_stock, created_at)
+             VALUES (1, 'material', 'out', 10, ?1, ?1 - 5, datetime('now'))
+             RETURNING id",
+        )
+        .bind(old_stock)
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE materials SET current_stock = current_stock - 5 WHERE id = 1")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+
+        let report = check_stock_ledger(&mut conn, false).await.unwrap();
+        assert_eq!(report.issues.len(), 1);
+        let issue = &report.issues[0];
+        assert_eq!(issue.kind, LedgerIssueKind::RowMismatch);
+        assert_eq!(issue.inventory_log_id, Some(log_id));
+        assert_eq!(
+            (issue.expected, issue.actual),
+            (old_stock - 10.0, old_stock - 5.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn unlogged_stock_gets_an_opening_adjustment() {
+        let pool = seeded().await;
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query(
+            "INSERT INTO materials (name, unit, current_stock, created_at)
+             VALUES ('舊原料', 'g', 50, datetime('now'))",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+
+        let report = check_stock_ledger(&mut conn, true).await.unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, LedgerIssueKind::BalanceMismatch);
+        assert_eq!(
+            (report.issues[0].expected, report.issues[0].actual),
+            (0.0, 50.0)
+        );
+        assert_eq!(report.corrections.len(), 1);
+
+        let report = check_stock_ledger(&mut conn, false).await.unwrap();
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+}
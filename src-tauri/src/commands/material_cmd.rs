@@ -1,21 +1,24 @@
-use sqlx::SqlitePool;
+use sqlx::{SqlitePool, Transaction};
 use tauri::State;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
 
+use crate::commands::code_cmd::{assign_sku, update_sku, SkuOwner};
+use crate::commands::formula_cmd::OIL_CATEGORY;
+use crate::error::AppError;
+use crate::models::formula::FattyAcidProfile;
 use crate::models::material::{
     CreateMaterialRequest, Material, RemoveMaterialRequest, UpdateMaterialRequest,
 };
 
 #[tauri::command]
-pub async fn list_materials(pool: State<'_, SqlitePool>) -> Result<Vec<Material>, String> {
+pub async fn list_materials(pool: State<'_, SqlitePool>) -> Result<Vec<Material>, AppError> {
     let rows = sqlx::query_as::<_, Material>(
-        "SELECT id, name, category, unit, current_stock, low_stock_alert, note, created_at, deleted_at 
+        "SELECT id, name, category, sku, unit, current_stock, low_stock_alert, sap_naoh, sap_koh, density, avg_cost, inci_name, saponified_inci_name, note, created_at, deleted_at 
          FROM materials 
          WHERE deleted_at IS NULL"
     )
     .fetch_all(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
 
     Ok(rows)
 }
@@ -24,14 +27,17 @@ pub async fn list_materials(pool: State<'_, SqlitePool>) -> Result<Vec<Material>
 pub async fn add_material(
     pool: State<'_, SqlitePool>,
     material: CreateMaterialRequest,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
+
     let created_at = chrono::Utc::now().to_rfc3339();
 
-    sqlx::query(
+    let material_id: i64 = sqlx::query_scalar(
         r#"
         INSERT INTO materials
-        (name, category, unit, current_stock, low_stock_alert, note, created_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?)
+        (name, category, unit, current_stock, low_stock_alert, sap_naoh, sap_koh, density, inci_name, saponified_inci_name, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
         "#,
     )
     .bind(&material.name)
@@ -39,11 +45,63 @@ pub async fn add_material(
     .bind(&material.unit)
     .bind(material.current_stock)
     .bind(material.low_stock_alert)
+    .bind(material.sap_naoh)
+    .bind(material.sap_koh)
+    .bind(material.density)
+    .bind(&material.inci_name)
+    .bind(&material.saponified_inci_name)
     .bind(&material.note)
     .bind(&created_at)
-    .execute(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .fetch_one(&mut *tx)
+    .await?;
+
+    assign_sku(
+        &mut tx,
+        SkuOwner::Material,
+        material_id,
+        material.sku.as_deref(),
+    )
+    .await?;
+
+    // the initial stock is logged as an inventory addition and becomes the material's opening lot
+    if material.current_stock > 0.0 {
+        let log_id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO inventory_logs (item_id, item_type, action_type, change_amount, old_stock, new_stock, note, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(material_id)
+        .bind("material")
+        .bind("in")
+        .bind(material.current_stock)
+        .bind(0.0)
+        .bind(material.current_stock)
+        .bind("Initial stock")
+        .bind(&created_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO material_lots
+            (material_id, received_at, quantity, remaining_quantity, inventory_log_id, note, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(material_id)
+        .bind(&created_at)
+        .bind(material.current_stock)
+        .bind(material.current_stock)
+        .bind(log_id)
+        .bind("期初庫存")
+        .bind(&created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
 
     Ok(())
 }
@@ -53,7 +111,7 @@ pub async fn remove_material(
     app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
     material: RemoveMaterialRequest,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let answer = app
         .dialog()
         .message("確定要刪除這個產品嗎？")
@@ -77,8 +135,7 @@ pub async fn remove_material(
     .bind(&deleted_at)
     .bind(&material.id)
     .execute(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
 
     Ok(())
 }
@@ -88,21 +145,22 @@ pub async fn update_material(
     pool: State<'_, SqlitePool>,
     id: i64,
     req: UpdateMaterialRequest,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     println!("Updating material id {}: {:?}", id, req);
 
-    if let Ok(material) = get_material(&*pool, id).await {
-        if material.unit != req.unit {
-            return Err("不能更改已有材料的單位".to_string());
-        }
-    } else {
-        return Err(format!("查無原料 id {}", id));
+    let material = get_material(&pool, id).await?;
+    if material.unit != req.unit {
+        return Err(AppError::invalid_field("unit", "不能更改已有材料的單位"));
     }
 
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
+
+    update_sku(&mut tx, SkuOwner::Material, id, req.sku.as_deref()).await?;
+
     sqlx::query(
         r#"
         UPDATE materials
-        SET name = ?, category = ?, unit = ?, low_stock_alert = ?, note = ?
+        SET name = ?, category = ?, unit = ?, low_stock_alert = ?, sap_naoh = ?, sap_koh = ?, density = ?, inci_name = ?, saponified_inci_name = ?, note = ?
         WHERE id = ?
         "#,
     )
@@ -110,25 +168,110 @@ pub async fn update_material(
     .bind(&req.category)
     .bind(&req.unit)
     .bind(req.low_stock_alert)
+    .bind(req.sap_naoh)
+    .bind(req.sap_koh)
+    .bind(req.density)
+    .bind(&req.inci_name)
+    .bind(&req.saponified_inci_name)
     .bind(&req.note)
     .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_material_fatty_acids(
+    pool: State<'_, SqlitePool>,
+    material_id: i64,
+) -> Result<Option<FattyAcidProfile>, AppError> {
+    let profile = sqlx::query_as::<_, FattyAcidProfile>(
+        "SELECT lauric, myristic, palmitic, stearic, ricinoleic, oleic, linoleic, linolenic
+         FROM material_fatty_acids
+         WHERE material_id = ?",
+    )
+    .bind(material_id)
+    .fetch_optional(&*pool)
+    .await?;
+
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn set_material_fatty_acids(
+    pool: State<'_, SqlitePool>,
+    material_id: i64,
+    profile: FattyAcidProfile,
+) -> Result<(), AppError> {
+    let material = get_material(&pool, material_id).await?;
+    if material.category != OIL_CATEGORY {
+        return Err(AppError::validation(format!(
+            "只有{}類原料可以設定脂肪酸組成",
+            OIL_CATEGORY
+        )));
+    }
+
+    let acids = [
+        profile.lauric,
+        profile.myristic,
+        profile.palmitic,
+        profile.stearic,
+        profile.ricinoleic,
+        profile.oleic,
+        profile.linoleic,
+        profile.linolenic,
+    ];
+    if acids.iter().any(|pct| !(0.0..=100.0).contains(pct)) {
+        return Err(AppError::validation("脂肪酸比例必須介於 0 到 100 之間"));
+    }
+    if acids.iter().sum::<f64>() > 100.0 + f64::EPSILON {
+        return Err(AppError::validation("脂肪酸比例總和不可超過 100"));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO material_fatty_acids
+        (material_id, lauric, myristic, palmitic, stearic, ricinoleic, oleic, linoleic, linolenic)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(material_id) DO UPDATE SET
+            lauric = excluded.lauric,
+            myristic = excluded.myristic,
+            palmitic = excluded.palmitic,
+            stearic = excluded.stearic,
+            ricinoleic = excluded.ricinoleic,
+            oleic = excluded.oleic,
+            linoleic = excluded.linoleic,
+            linolenic = excluded.linolenic
+        "#,
+    )
+    .bind(material_id)
+    .bind(profile.lauric)
+    .bind(profile.myristic)
+    .bind(profile.palmitic)
+    .bind(profile.stearic)
+    .bind(profile.ricinoleic)
+    .bind(profile.oleic)
+    .bind(profile.linoleic)
+    .bind(profile.linolenic)
     .execute(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
 
     Ok(())
 }
 
-async fn get_material(pool: &SqlitePool, id: i64) -> Result<Material, String> {
+async fn get_material(pool: &SqlitePool, id: i64) -> Result<Material, AppError> {
     let material = sqlx::query_as::<_, Material>(
-        "SELECT id, name, category, unit, current_stock, low_stock_alert, note, created_at, deleted_at 
+        "SELECT id, name, category, sku, unit, current_stock, low_stock_alert, sap_naoh, sap_koh, density, avg_cost, inci_name, saponified_inci_name, note, created_at, deleted_at 
          FROM materials 
          WHERE id = ? AND deleted_at IS NULL",
     )
     .bind(id)
-    .fetch_one(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .fetch_optional(&*pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("查無原料 id {}", id)))?;
 
     Ok(material)
 }
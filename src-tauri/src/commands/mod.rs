@@ -1,4 +1,18 @@
+pub mod code_cmd;
+pub mod cost_cmd;
+pub mod customer_cmd;
 pub mod db_cmd;
+pub mod formula_cmd;
+pub mod label_cmd;
+pub mod ledger_cmd;
 pub mod material_cmd;
 pub mod movement_cmd;
 pub mod product_cmd;
+pub mod production_cmd;
+pub mod purchase_cmd;
+pub mod recipe_cmd;
+pub mod sales_cmd;
+pub mod settings_cmd;
+pub mod stocktake_cmd;
+pub mod supplier_cmd;
+pub mod trace_cmd;
@@ -1,45 +1,56 @@
-use sqlx::{SqlitePool, Transaction};
+use sqlx::{SqliteConnection, SqlitePool, Transaction};
 use tauri::State;
 
-use crate::models::movement::{CreateMovementRequest, Movement};
+use crate::commands::settings_cmd::{
+    read_setting, MATERIAL_NEGATIVE_STOCK_POLICY, PRODUCT_NEGATIVE_STOCK_POLICY,
+};
+use crate::error::AppError;
+use crate::models::movement::{
+    ActionType, CreateMovementRequest, ItemType, Movement, MovementLink, MovementResult,
+    NegativeStockPolicy, StockShortage,
+};
+
+/// Policy used until one is saved in `app_settings`
+const DEFAULT_NEGATIVE_STOCK_POLICY: NegativeStockPolicy = NegativeStockPolicy::Warn;
+
+/// A movement written by `apply_material_movement` or `apply_product_movement`
+pub(crate) struct MovementOutcome {
+    pub log_id: i64,
+    /// Shortage let through under the `warn` negative-stock policy
+    pub warning: Option<StockShortage>,
+}
+
+/// Selects inventory logs with their item details; append WHERE / ORDER BY clauses
+pub(crate) const MOVEMENT_SELECT: &str = "
+    SELECT
+        il.*,
+        COALESCE(m.name, p.name) as item_name,
+        COALESCE(m.unit, p.unit) as item_unit,
+        COALESCE(m.category, p.category) as item_category,
+        pb.lot_code as related_batch,
+        CASE WHEN orig.voided_by = il.id THEN orig.id END as void_of
+    FROM inventory_logs il
+    LEFT JOIN materials m ON il.item_type = 'material' AND il.item_id = m.id
+    LEFT JOIN products p ON il.item_type = 'product' AND il.item_id = p.id
+    LEFT JOIN production_batches pb ON il.batch_id = pb.id
+    LEFT JOIN inventory_logs orig ON il.reversal_of = orig.id";
 
 #[tauri::command]
-pub async fn list_movements(pool: State<'_, SqlitePool>) -> Result<Vec<Movement>, String> {
-    let rows = sqlx::query_as::<_, Movement>(
-        "SELECT 
-            il.*,
-            COALESCE(m.name, p.name) as item_name,
-            COALESCE(m.unit, p.unit) as item_unit,
-            COALESCE(m.category, p.category) as item_category
-        FROM inventory_logs il
-        LEFT JOIN materials m ON il.item_type = 'material' AND il.item_id = m.id
-        LEFT JOIN products p ON il.item_type = 'product' AND il.item_id = p.id
-        ORDER BY il.created_at DESC",
-    )
-    .fetch_all(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+pub async fn list_movements(pool: State<'_, SqlitePool>) -> Result<Vec<Movement>, AppError> {
+    let sql = format!("{} ORDER BY il.created_at DESC", MOVEMENT_SELECT);
+    let rows = sqlx::query_as::<_, Movement>(&sql)
+        .fetch_all(&*pool)
+        .await?;
 
     Ok(rows)
 }
 
 #[tauri::command]
-pub async fn list_recent_movements(pool: State<'_, SqlitePool>) -> Result<Vec<Movement>, String> {
-    let rows = sqlx::query_as::<_, Movement>(
-        "SELECT 
-            il.*,
-            COALESCE(m.name, p.name) as item_name,
-            COALESCE(m.unit, p.unit) as item_unit,
-            COALESCE(m.category, p.category) as item_category
-        FROM inventory_logs il
-        LEFT JOIN materials m ON il.item_type = 'material' AND il.item_id = m.id
-        LEFT JOIN products p ON il.item_type = 'product' AND il.item_id = p.id
-        ORDER BY il.created_at DESC
-        LIMIT 10",
-    )
-    .fetch_all(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+pub async fn list_recent_movements(pool: State<'_, SqlitePool>) -> Result<Vec<Movement>, AppError> {
+    let sql = format!("{} ORDER BY il.created_at DESC LIMIT 10", MOVEMENT_SELECT);
+    let rows = sqlx::query_as::<_, Movement>(&sql)
+        .fetch_all(&*pool)
+        .await?;
 
     Ok(rows)
 }
@@ -48,133 +59,788 @@ pub async fn list_recent_movements(pool: State<'_, SqlitePool>) -> Result<Vec<Mo
 pub async fn add_inventory(
     pool: State<'_, SqlitePool>,
     req: CreateMovementRequest,
-) -> Result<(), String> {
+) -> Result<MovementResult, AppError> {
     // Fetch the item based on type
-    if req.item_type == "material" {
-        add_material_inventory(pool, req).await?
+    if req.item_type == ItemType::Material {
+        add_material_inventory(pool, req).await
     } else {
-        add_product_inventory(pool, req).await?
+        add_product_inventory(pool, req).await
     }
-
-    Ok(())
 }
 
 #[tauri::command]
 pub async fn add_product_inventory(
     pool: State<'_, SqlitePool>,
     req: CreateMovementRequest,
-) -> Result<(), String> {
-    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+) -> Result<MovementResult, AppError> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
+
+    let outcome = apply_product_movement(&mut tx, &req, MovementLink::default()).await?;
+    let movement = fetch_movement(&mut tx, outcome.log_id).await?;
+
+    tx.commit().await?;
+    Ok(MovementResult {
+        movement,
+        warning: outcome.warning,
+    })
+}
 
+#[tauri::command]
+pub async fn add_material_inventory(
+    pool: State<'_, SqlitePool>,
+    req: CreateMovementRequest,
+) -> Result<MovementResult, AppError> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
+
+    let outcome = apply_material_movement(&mut tx, &req, MovementLink::default()).await?;
+    let movement = fetch_movement(&mut tx, outcome.log_id).await?;
+
+    tx.commit().await?;
+    Ok(MovementResult {
+        movement,
+        warning: outcome.warning,
+    })
+}
+
+/// Voids a movement entered by mistake. A reversing entry linked to the original
+/// undoes its stock change and the original is marked voided, so both stay in
+/// the history. Movements made by a purchase order, sale, production batch or
+/// stocktake have to be corrected through that record instead.
+/// Returns the reversing entry. Voiding stock that has since been used is
+/// subject to the negative-stock policy.
+#[tauri::command]
+pub async fn void_movement(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    note: Option<String>,
+) -> Result<MovementResult, AppError> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
+
+    let original = fetch_movement(&mut tx, id).await?;
+    if original.voided_at.is_some() {
+        return Err(AppError::Conflict(format!("異動紀錄 #{} 已經作廢", id)));
+    }
+    if original.reversal_of.is_some() {
+        return Err(AppError::Conflict(format!(
+            "異動紀錄 #{} 是沖銷紀錄，無法作廢",
+            id
+        )));
+    }
+    let source = [
+        original
+            .purchase_order_id
+            .map(|id| format!("採購單 #{}", id)),
+        original.sales_order_id.map(|id| format!("銷售單 #{}", id)),
+        original.batch_id.map(|id| format!("生產批次 #{}", id)),
+        original
+            .stocktake_session_id
+            .map(|id| format!("盤點單 #{}", id)),
+    ]
+    .into_iter()
+    .flatten()
+    .next();
+    if let Some(source) = source {
+        return Err(AppError::Conflict(format!(
+            "異動紀錄 #{} 由{}產生，請由該單據更正",
+            id, source
+        )));
+    }
+
+    // Stock the original added, negative when it removed stock
+    let added = original.new_stock - original.old_stock;
+    let table = original.item_type.table();
+
+    let sql = format!(
+        "SELECT CAST(current_stock AS REAL) FROM {} WHERE id = ?",
+        table
+    );
+    let old_stock: f64 = sqlx::query_scalar(&sql)
+        .bind(original.item_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let new_stock = old_stock - added;
+
+    let req = CreateMovementRequest {
+        note: note
+            .filter(|note| !note.trim().is_empty())
+            .or_else(|| Some(format!("作廢異動 #{}", id))),
+        ..CreateMovementRequest::new(
+            original.item_type,
+            original.item_id,
+            if added > 0.0 {
+                ActionType::Out
+            } else {
+                ActionType::In
+            },
+            added.abs(),
+        )
+    };
+    let warning = check_negative_stock(&mut tx, &req, old_stock, new_stock).await?;
+
+    let link = MovementLink {
+        reversal_of: Some(id),
+        ..Default::default()
+    };
+    let log_id = insert_inventory_log(&mut tx, &req, old_stock, new_stock, link).await?;
+
+    if original.item_type == ItemType::Material {
+        if added > 0.0 {
+            if let Some(unit_cost) = original.unit_cost {
+                remove_from_average_cost(&mut tx, original.item_id, old_stock, added, unit_cost)
+                    .await?;
+            }
+            take_back_material_lots(&mut tx, original.item_id, id, added, log_id).await?;
+        } else if added < 0.0 {
+            restore_material_lots(&mut tx, id, -added, log_id).await?;
+        }
+        sqlx::query("UPDATE materials SET current_stock = ? WHERE id = ?")
+            .bind(new_stock)
+            .bind(original.item_id)
+            .execute(&mut *tx)
+            .await?;
+    } else {
+        if added < 0.0 {
+            restore_batches(&mut tx, id, -added as i64, log_id).await?;
+        }
+        sqlx::query("UPDATE products SET current_stock = ? WHERE id = ?")
+            .bind(new_stock as i64)
+            .bind(original.item_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query("UPDATE inventory_logs SET voided_at = ?, voided_by = ? WHERE id = ?")
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(log_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    let reversal = fetch_movement(&mut tx, log_id).await?;
+
+    tx.commit().await?;
+
+    Ok(MovementResult {
+        movement: reversal,
+        warning,
+    })
+}
+
+async fn fetch_movement(conn: &mut SqliteConnection, id: i64) -> Result<Movement, AppError> {
+    let sql = format!("{} WHERE il.id = ?", MOVEMENT_SELECT);
+    sqlx::query_as::<_, Movement>(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("查無異動紀錄 id {}", id)))
+}
+
+/// Logs a product movement and updates its stock inside the caller's transaction,
+/// subject to the product negative-stock policy.
+pub(crate) async fn apply_product_movement(
+    conn: &mut SqliteConnection,
+    req: &CreateMovementRequest,
+    link: MovementLink,
+) -> Result<MovementOutcome, AppError> {
     // Get current stock before change
     let old_stock: i64 = sqlx::query_scalar("SELECT current_stock FROM products WHERE id = ?")
         .bind(req.item_id)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(|e| e.to_string())?;
+        .fetch_one(&mut *conn)
+        .await?;
 
     // Calculate new stock
-    let new_stock = match req.action_type.as_str() {
-        "in" => old_stock + req.change_amount as i64,
-        "out" => old_stock - req.change_amount as i64,
-        "adj" => req.change_amount as i64, // adjustment sets absolute value
-        _ => return Err("Invalid action type".to_string()),
+    let new_stock = match req.action_type {
+        ActionType::In | ActionType::Production | ActionType::Return => {
+            old_stock + req.change_amount as i64
+        }
+        ActionType::Out | ActionType::Sale | ActionType::Waste | ActionType::Quarantine => {
+            old_stock - req.change_amount as i64
+        }
+        ActionType::Adj => req.change_amount as i64, // adjustment sets absolute value
     };
 
+    let warning = check_negative_stock(conn, req, old_stock as f64, new_stock as f64).await?;
+
+    // Bars from unreleased batches are still curing and cannot leave the shelf
+    if matches!(
+        req.action_type,
+        ActionType::Out | ActionType::Sale | ActionType::Waste
+    ) {
+        let curing_stock: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(quantity), 0) FROM production_batches
+             WHERE product_id = ? AND released_at IS NULL",
+        )
+        .bind(req.item_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if curing_stock > 0 && new_stock < curing_stock {
+            return Err(AppError::Conflict(format!(
+                "可售庫存不足：可售 {}，熟成中 {}",
+                (old_stock - curing_stock).max(0),
+                curing_stock
+            )));
+        }
+    }
+
     // 1. Record inventory change log
-    let now = chrono::Utc::now().to_rfc3339();
+    let log_id = insert_inventory_log(conn, req, old_stock as f64, new_stock as f64, link).await?;
+
+    if matches!(
+        req.action_type,
+        ActionType::Out | ActionType::Sale | ActionType::Waste
+    ) {
+        allocate_batches(conn, req.item_id, old_stock - new_stock, log_id).await?;
+    }
+
+    // 2. Update product stock; quarantined bars move to their own bucket
+    let quarantined = if req.action_type == ActionType::Quarantine {
+        old_stock - new_stock
+    } else {
+        0
+    };
     sqlx::query(
         r#"
-        INSERT INTO inventory_logs (item_id, item_type, action_type, change_amount, old_stock, new_stock, note, created_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        UPDATE products
+        SET current_stock = ?, quarantine_stock = quarantine_stock + ?
+        WHERE id = ?
         "#,
     )
-    .bind(req.item_id)
-    .bind(&req.item_type)
-    .bind(&req.action_type)
-    .bind(req.change_amount)
-    .bind(old_stock)
     .bind(new_stock)
-    .bind(req.note.as_deref())
-    .bind(&now)
-    .execute(&mut *tx)
-    .await
-    .map_err(|e| e.to_string())?;
+    .bind(quarantined)
+    .bind(req.item_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(MovementOutcome { log_id, warning })
+}
+
+/// Logs a material movement and updates its stock inside the caller's transaction,
+/// subject to the material negative-stock policy.
+pub(crate) async fn apply_material_movement(
+    conn: &mut SqliteConnection,
+    req: &CreateMovementRequest,
+    link: MovementLink,
+) -> Result<MovementOutcome, AppError> {
+    // Get current stock before change
+    let old_stock: f64 = sqlx::query_scalar("SELECT current_stock FROM materials WHERE id = ?")
+        .bind(req.item_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    if req.unit_cost.is_some_and(|cost| cost < 0.0) {
+        return Err(AppError::invalid_field("unit_cost", "單位成本不可為負數"));
+    }
+
+    // Calculate new stock
+    let new_stock = match req.action_type {
+        ActionType::In => old_stock + req.change_amount,
+        ActionType::Out | ActionType::Waste => old_stock - req.change_amount,
+        ActionType::Adj => req.change_amount, // adjustment sets absolute value
+        action_type => {
+            return Err(AppError::invalid_field(
+                "action_type",
+                format!("原料不支援 {} 異動", action_type.as_str()),
+            ))
+        }
+    };
+    let warning = check_negative_stock(conn, req, old_stock, new_stock).await?;
 
-    // 2. Update product stock
+    // 1. Record inventory change log
+    let log_id = insert_inventory_log(conn, req, old_stock, new_stock, link).await?;
+
+    if req.action_type == ActionType::In {
+        update_average_cost(conn, req, old_stock).await?;
+    }
+
+    // Stock added becomes a new lot, stock removed is drawn from existing lots
+    if new_stock > old_stock {
+        receive_material_lot(conn, req, new_stock - old_stock, log_id).await?;
+    } else if new_stock < old_stock {
+        draw_material_lots(conn, req.item_id, old_stock - new_stock, log_id).await?;
+    }
+
+    // 2. Update material stock
     sqlx::query(
         r#"
-        UPDATE products
+        UPDATE materials
         SET current_stock = ?
         WHERE id = ?
         "#,
     )
     .bind(new_stock)
     .bind(req.item_id)
-    .execute(&mut *tx)
-    .await
-    .map_err(|e| e.to_string())?;
+    .execute(&mut *conn)
+    .await?;
 
-    tx.commit().await.map_err(|e| e.to_string())?;
-    Ok(())
+    Ok(MovementOutcome { log_id, warning })
 }
 
-#[tauri::command]
-pub async fn add_material_inventory(
-    pool: State<'_, SqlitePool>,
-    req: CreateMovementRequest,
-) -> Result<(), String> {
-    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+/// Applies the item type's negative-stock policy to a movement taking stock from
+/// `old_stock` to `new_stock`. Movements that do not lower stock below zero
+/// always pass. Returns the shortage to warn about under the `warn` policy.
+async fn check_negative_stock(
+    conn: &mut SqliteConnection,
+    req: &CreateMovementRequest,
+    old_stock: f64,
+    new_stock: f64,
+) -> Result<Option<StockShortage>, AppError> {
+    if new_stock >= 0.0 || new_stock >= old_stock {
+        return Ok(None);
+    }
 
-    // Get current stock before change
-    let old_stock: f64 = sqlx::query_scalar("SELECT current_stock FROM materials WHERE id = ?")
-        .bind(req.item_id)
-        .fetch_one(&mut *tx)
+    let policy = read_negative_stock_policy(conn, req.item_type).await?;
+    if policy == NegativeStockPolicy::Allow {
+        return Ok(None);
+    }
+
+    let shortage = find_shortage(
+        conn,
+        req.item_type,
+        req.item_id,
+        old_stock,
+        old_stock - new_stock,
+    )
+    .await?;
+    match policy {
+        NegativeStockPolicy::Reject => Err(AppError::InsufficientStock(shortage)),
+        _ => Ok(Some(shortage)),
+    }
+}
+
+async fn read_negative_stock_policy(
+    conn: &mut SqliteConnection,
+    item_type: ItemType,
+) -> Result<NegativeStockPolicy, AppError> {
+    let key = if item_type == ItemType::Material {
+        MATERIAL_NEGATIVE_STOCK_POLICY
+    } else {
+        PRODUCT_NEGATIVE_STOCK_POLICY
+    };
+
+    match read_setting(conn, key)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(AppError::Database)?
+        .as_deref()
+        .map(str::trim)
+    {
+        None | Some("") => Ok(DEFAULT_NEGATIVE_STOCK_POLICY),
+        Some("reject") => Ok(NegativeStockPolicy::Reject),
+        Some("warn") => Ok(NegativeStockPolicy::Warn),
+        Some("allow") => Ok(NegativeStockPolicy::Allow),
+        Some(value) => Err(AppError::validation(format!(
+            "設定 {} 的值無效: {}",
+            key, value
+        ))),
+    }
+}
 
-    // Calculate new stock
-    let new_stock = match req.action_type.as_str() {
-        "in" => old_stock + req.change_amount,
-        "out" => old_stock - req.change_amount,
-        "adj" => req.change_amount, // adjustment sets absolute value
-        _ => return Err("Invalid action type".to_string()),
+/// Describes an item that would end up below zero after `requested` leaves it
+async fn find_shortage(
+    conn: &mut SqliteConnection,
+    item_type: ItemType,
+    item_id: i64,
+    current_stock: f64,
+    requested: f64,
+) -> Result<StockShortage, AppError> {
+    let sql = format!("SELECT name, unit FROM {} WHERE id = ?", item_type.table());
+    let (item_name, unit): (String, String) = sqlx::query_as(&sql)
+        .bind(item_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(StockShortage {
+        item_type,
+        item_id,
+        item_name,
+        unit,
+        current_stock,
+        requested,
+        shortfall: requested - current_stock,
+    })
+}
+
+/// Folds a costed receipt into the material's moving weighted-average cost.
+/// Receipts without a unit cost leave the average unchanged.
+async fn update_average_cost(
+    conn: &mut SqliteConnection,
+    req: &CreateMovementRequest,
+    old_stock: f64,
+) -> Result<(), AppError> {
+    let Some(unit_cost) = req.unit_cost else {
+        return Ok(());
     };
 
-    // 1. Record inventory change log
+    let avg_cost: Option<f64> = sqlx::query_scalar("SELECT avg_cost FROM materials WHERE id = ?")
+        .bind(req.item_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    // Negative or uncosted stock carries no value into the new average
+    let new_avg = match avg_cost {
+        Some(avg_cost) if old_stock > 0.0 => {
+            (old_stock * avg_cost + req.change_amount * unit_cost) / (old_stock + req.change_amount)
+        }
+        _ => unit_cost,
+    };
+
+    sqlx::query("UPDATE materials SET avg_cost = ? WHERE id = ?")
+        .bind(new_avg)
+        .bind(req.item_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+async fn receive_material_lot(
+    conn: &mut SqliteConnection,
+    req: &CreateMovementRequest,
+    quantity: f64,
+    log_id: i64,
+) -> Result<i64, AppError> {
+    if let Some(expires_at) = &req.expires_at {
+        chrono::NaiveDate::parse_from_str(expires_at, "%Y-%m-%d").map_err(|_| {
+            AppError::invalid_field(
+                "expires_at",
+                format!("到期日格式錯誤: {} (應為 YYYY-MM-DD)", expires_at),
+            )
+        })?;
+    }
+
     let now = chrono::Utc::now().to_rfc3339();
-    sqlx::query(
+    let received_at = req.received_at.clone().unwrap_or_else(|| now.clone());
+    let note = (req.action_type != ActionType::In).then_some("庫存校準");
+
+    let lot_id: i64 = sqlx::query_scalar(
         r#"
-        INSERT INTO inventory_logs (item_id, item_type, action_type, change_amount, old_stock, new_stock, note, created_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO material_lots
+        (material_id, lot_code, received_at, expires_at, quantity, remaining_quantity, inventory_log_id, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
         "#,
     )
     .bind(req.item_id)
-    .bind(&req.item_type)
-    .bind(&req.action_type)
-    .bind(req.change_amount)
-    .bind(old_stock)
-    .bind(new_stock)
-    .bind(req.note.as_deref())
+    .bind(req.lot_code.as_deref())
+    .bind(&received_at)
+    .bind(req.expires_at.as_deref())
+    .bind(quantity)
+    .bind(quantity)
+    .bind(log_id)
+    .bind(note)
     .bind(&now)
-    .execute(&mut *tx)
-    .await
-    .map_err(|e| e.to_string())?;
+    .fetch_one(&mut *conn)
+    .await?;
 
-    // 2. Update material stock
-    sqlx::query(
+    Ok(lot_id)
+}
+
+/// Draws a quantity from a material's lots, first expired first out, and records
+/// which lots the log row consumed. Lots without an expiry date go last, oldest
+/// first. Stock that predates lot tracking is left unallocated.
+async fn draw_material_lots(
+    conn: &mut SqliteConnection,
+    material_id: i64,
+    quantity: f64,
+    log_id: i64,
+) -> Result<(), AppError> {
+    let lots: Vec<(i64, f64)> = sqlx::query_as(
+        "SELECT id, remaining_quantity FROM material_lots
+         WHERE material_id = ? AND remaining_quantity > 0
+         ORDER BY expires_at IS NULL, expires_at, received_at, id",
+    )
+    .bind(material_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    consume_lots(conn, lots, quantity, log_id).await?;
+
+    Ok(())
+}
+
+/// Takes stock back out of the lots in order, recording what `log_id` consumed.
+/// Returns the quantity the lots could not cover.
+async fn consume_lots(
+    conn: &mut SqliteConnection,
+    lots: Vec<(i64, f64)>,
+    quantity: f64,
+    log_id: i64,
+) -> Result<f64, AppError> {
+    let mut outstanding = quantity;
+    for (lot_id, remaining) in lots {
+        if outstanding <= 0.0 {
+            break;
+        }
+        let take = remaining.min(outstanding);
+        outstanding -= take;
+
+        sqlx::query(
+            "INSERT INTO lot_consumptions (inventory_log_id, material_lot_id, quantity) VALUES (?, ?, ?)",
+        )
+        .bind(log_id)
+        .bind(lot_id)
+        .bind(take)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "UPDATE material_lots SET remaining_quantity = remaining_quantity - ? WHERE id = ?",
+        )
+        .bind(take)
+        .bind(lot_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(outstanding)
+}
+
+/// Removes stock a voided movement added: first from the lot that movement
+/// received, then, if part of that lot is already used, from the other lots.
+async fn take_back_material_lots(
+    conn: &mut SqliteConnection,
+    material_id: i64,
+    original_log_id: i64,
+    quantity: f64,
+    log_id: i64,
+) -> Result<(), AppError> {
+    let lots: Vec<(i64, f64)> = sqlx::query_as(
+        "SELECT id, remaining_quantity FROM material_lots
+         WHERE inventory_log_id = ? AND remaining_quantity > 0",
+    )
+    .bind(original_log_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let outstanding = consume_lots(conn, lots, quantity, log_id).await?;
+    if outstanding > 0.0 {
+        draw_material_lots(conn, material_id, outstanding, log_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Puts stock a voided movement removed back into the lots it was drawn from.
+/// The returned stock is recorded against `log_id` as negative consumptions.
+async fn restore_material_lots(
+    conn: &mut SqliteConnection,
+    original_log_id: i64,
+    quantity: f64,
+    log_id: i64,
+) -> Result<(), AppError> {
+    let consumptions: Vec<(i64, f64)> = sqlx::query_as(
+        "SELECT material_lot_id, quantity FROM lot_consumptions
+         WHERE inventory_log_id = ?
+         ORDER BY id DESC",
+    )
+    .bind(original_log_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut outstanding = quantity;
+    for (lot_id, consumed) in consumptions {
+        if outstanding <= 0.0 {
+            break;
+        }
+        let give_back = consumed.min(outstanding);
+        outstanding -= give_back;
+
+        sqlx::query(
+            "INSERT INTO lot_consumptions (inventory_log_id, material_lot_id, quantity) VALUES (?, ?, ?)",
+        )
+        .bind(log_id)
+        .bind(lot_id)
+        .bind(-give_back)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "UPDATE material_lots SET remaining_quantity = remaining_quantity + ? WHERE id = ?",
+        )
+        .bind(give_back)
+        .bind(lot_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Takes a voided costed receipt back out of the material's moving average.
+/// The average is left alone when no stock would remain to carry it.
+async fn remove_from_average_cost(
+    conn: &mut SqliteConnection,
+    material_id: i64,
+    stock: f64,
+    quantity: f64,
+    unit_cost: f64,
+) -> Result<(), AppError> {
+    let avg_cost: Option<f64> = sqlx::query_scalar("SELECT avg_cost FROM materials WHERE id = ?")
+        .bind(material_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let remaining = stock - quantity;
+    let Some(avg_cost) = avg_cost.filter(|_| remaining > 0.0) else {
+        return Ok(());
+    };
+    let new_avg = (stock * avg_cost - quantity * unit_cost) / remaining;
+    if new_avg < 0.0 {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE materials SET avg_cost = ? WHERE id = ?")
+        .bind(new_avg)
+        .bind(material_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Allocates units leaving product stock to released production batches, oldest
+/// first, so each sale can be traced to a batch. Units not made in a batch stay
+/// unallocated.
+async fn allocate_batches(
+    conn: &mut SqliteConnection,
+    product_id: i64,
+    quantity: i64,
+    log_id: i64,
+) -> Result<(), AppError> {
+    let batches: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT id, remaining_quantity FROM production_batches
+         WHERE product_id = ? AND released_at IS NOT NULL AND remaining_quantity > 0
+         ORDER BY produced_at, id",
+    )
+    .bind(product_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut outstanding = quantity;
+    for (batch_id, remaining) in batches {
+        if outstanding <= 0 {
+            break;
+        }
+        let take = remaining.min(outstanding);
+        outstanding -= take;
+
+        sqlx::query(
+            "INSERT INTO batch_allocations (inventory_log_id, batch_id, quantity) VALUES (?, ?, ?)",
+        )
+        .bind(log_id)
+        .bind(batch_id)
+        .bind(take)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "UPDATE production_batches SET remaining_quantity = remaining_quantity - ? WHERE id = ?",
+        )
+        .bind(take)
+        .bind(batch_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Puts units that came back from a product movement into the batches that
+/// movement drew them from, newest allocation first. The returned units are
+/// recorded against `log_id` as negative allocations.
+pub(crate) async fn restore_batches(
+    conn: &mut SqliteConnection,
+    original_log_id: i64,
+    quantity: i64,
+    log_id: i64,
+) -> Result<(), AppError> {
+    // What the original movement still has allocated after earlier returns
+    let allocations: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT ba.batch_id,
+                ba.quantity + COALESCE((
+                    SELECT SUM(back.quantity) FROM batch_allocations back
+                    JOIN inventory_logs il ON back.inventory_log_id = il.id
+                    WHERE il.reversal_of = ba.inventory_log_id AND back.batch_id = ba.batch_id
+                ), 0) as outstanding
+         FROM batch_allocations ba
+         WHERE ba.inventory_log_id = ?
+         ORDER BY ba.id DESC",
+    )
+    .bind(original_log_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut outstanding = quantity;
+    for (batch_id, allocated) in allocations {
+        if outstanding <= 0 {
+            break;
+        }
+        let give_back = allocated.min(outstanding);
+        if give_back <= 0 {
+            continue;
+        }
+        outstanding -= give_back;
+
+        sqlx::query(
+            "INSERT INTO batch_allocations (inventory_log_id, batch_id, quantity) VALUES (?, ?, ?)",
+        )
+        .bind(log_id)
+        .bind(batch_id)
+        .bind(-give_back)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "UPDATE production_batches SET remaining_quantity = remaining_quantity + ? WHERE id = ?",
+        )
+        .bind(give_back)
+        .bind(batch_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Writes an inventory log row without touching stock; returns its id
+pub(crate) async fn insert_inventory_log(
+    conn: &mut SqliteConnection,
+    req: &CreateMovementRequest,
+    old_stock: f64,
+    new_stock: f64,
+    link: MovementLink,
+) -> Result<i64, AppError> {
+    let now = chrono::Utc::now().to_rfc3339();
+    // Adjustments log the delta they caused and keep the requested level aside
+    let (change_amount, target_stock) = if req.action_type == ActionType::Adj {
+        (new_stock - old_stock, Some(req.change_amount))
+    } else {
+        (req.change_amount, None)
+    };
+    let log_id: i64 = sqlx::query_scalar(
         r#"
-        UPDATE materials
-        SET current_stock = ?
-        WHERE id = ?
+        INSERT INTO inventory_logs (item_id, item_type, action_type, change_amount, target_stock, old_stock, new_stock, note, unit_cost, batch_id, purchase_order_id, sales_order_id, reversal_of, stocktake_session_id, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
         "#,
     )
-    .bind(new_stock)
     .bind(req.item_id)
-    .execute(&mut *tx)
-    .await
-    .map_err(|e| e.to_string())?;
+    .bind(req.item_type)
+    .bind(req.action_type)
+    .bind(change_amount)
+    .bind(target_stock)
+    .bind(old_stock)
+    .bind(new_stock)
+    .bind(req.note.as_deref())
+    .bind(req.unit_cost)
+    .bind(link.batch_id)
+    .bind(link.purchase_order_id)
+    .bind(link.sales_order_id)
+    .bind(link.reversal_of)
+    .bind(link.stocktake_session_id)
+    .bind(&now)
+    .fetch_one(&mut *conn)
+    .await?;
 
-    tx.commit().await.map_err(|e| e.to_string())?;
-    Ok(())
+    Ok(log_id)
 }
@@ -2,20 +2,30 @@ use sqlx::{SqlitePool, Transaction};
 use tauri::State;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
 
+use crate::commands::code_cmd::{assign_sku, update_sku, SkuOwner};
+use crate::error::{AppError, FieldError};
 use crate::models::product::{
-    CreateProductRequest, Product, RemoveProductRequest, UpdateProductRequest,
+    CreateProductRequest, Product, ProductPrice, RemoveProductRequest, UpdateProductRequest,
 };
 
+/// Selects products with their curing and sellable quantities; append a WHERE clause
+pub(crate) const PRODUCT_SELECT: &str = "
+    SELECT p.id, p.name, p.category, p.sku, p.unit, p.current_stock,
+           COALESCE(c.curing, 0) as curing_stock,
+           MAX(p.current_stock - COALESCE(c.curing, 0), 0) as available_stock,
+           p.quarantine_stock, p.retail_price, p.wholesale_price, p.note, p.created_at, p.deleted_at
+    FROM products p
+    LEFT JOIN (
+        SELECT product_id, SUM(quantity) as curing
+        FROM production_batches
+        WHERE released_at IS NULL
+        GROUP BY product_id
+    ) c ON c.product_id = p.id";
+
 #[tauri::command]
-pub async fn list_products(pool: State<'_, SqlitePool>) -> Result<Vec<Product>, String> {
-    let rows = sqlx::query_as::<_, Product>(
-        "SELECT id, name, category, unit, current_stock, note, created_at, deleted_at 
-         FROM products 
-         WHERE deleted_at IS NULL",
-    )
-    .fetch_all(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+pub async fn list_products(pool: State<'_, SqlitePool>) -> Result<Vec<Product>, AppError> {
+    let sql = format!("{} WHERE p.deleted_at IS NULL", PRODUCT_SELECT);
+    let rows = sqlx::query_as::<_, Product>(&sql).fetch_all(&*pool).await?;
 
     Ok(rows)
 }
@@ -24,15 +34,17 @@ pub async fn list_products(pool: State<'_, SqlitePool>) -> Result<Vec<Product>,
 pub async fn add_product(
     pool: State<'_, SqlitePool>,
     product: CreateProductRequest,
-) -> Result<(), String> {
-    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
+
+    validate_prices(product.retail_price, product.wholesale_price)?;
 
     let created_at = chrono::Utc::now().to_rfc3339();
 
     let last_insert_id: i64 = sqlx::query_scalar(
         r#"
-        INSERT INTO products (name, category, unit, current_stock, note, created_at)
-        VALUES (?, ?, ?, ?, ?, ?)
+        INSERT INTO products (name, category, unit, current_stock, retail_price, wholesale_price, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         RETURNING id
         "#,
     )
@@ -40,11 +52,31 @@ pub async fn add_product(
     .bind(&product.category)
     .bind(&product.unit)
     .bind(product.current_stock)
+    .bind(product.retail_price)
+    .bind(product.wholesale_price)
     .bind(&product.note)
     .bind(&created_at)
     .fetch_one(&mut *tx)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
+
+    assign_sku(
+        &mut tx,
+        SkuOwner::Product,
+        last_insert_id,
+        product.sku.as_deref(),
+    )
+    .await?;
+
+    if product.retail_price.is_some() || product.wholesale_price.is_some() {
+        record_price(
+            &mut tx,
+            last_insert_id,
+            product.retail_price,
+            product.wholesale_price,
+            &created_at,
+        )
+        .await?;
+    }
 
     // if the initial stock is greater than zero, log it as an inventory addition
     if product.current_stock > 0 {
@@ -64,11 +96,10 @@ pub async fn add_product(
         .bind(Some("Initial stock".to_string()).as_deref())
         .bind(&now)
         .execute(&mut *tx)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
     }
 
-    tx.commit().await.map_err(|e| e.to_string())?;
+    tx.commit().await?;
 
     Ok(())
 }
@@ -78,38 +109,124 @@ pub async fn update_product(
     pool: State<'_, SqlitePool>,
     id: i64,
     req: UpdateProductRequest,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     println!("Updating product id {}: {:?}", id, req);
 
+    validate_prices(req.retail_price, req.wholesale_price)?;
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
+
+    let (retail_price, wholesale_price): (Option<f64>, Option<f64>) =
+        sqlx::query_as("SELECT retail_price, wholesale_price FROM products WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("查無產品 id {}", id)))?;
+
+    update_sku(&mut tx, SkuOwner::Product, id, req.sku.as_deref()).await?;
+
     sqlx::query(
         r#"
         UPDATE products
-        SET name = ?, category = ?, note = ?
+        SET name = ?, category = ?, retail_price = ?, wholesale_price = ?, note = ?
         WHERE id = ?
         "#,
     )
     .bind(&req.name)
     .bind(&req.category)
+    .bind(req.retail_price)
+    .bind(req.wholesale_price)
     .bind(&req.note)
     .bind(id)
-    .execute(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .execute(&mut *tx)
+    .await?;
+
+    // Only actual price changes go into the history
+    if retail_price != req.retail_price || wholesale_price != req.wholesale_price {
+        let now = chrono::Utc::now().to_rfc3339();
+        record_price(&mut tx, id, req.retail_price, req.wholesale_price, &now).await?;
+    }
+
+    tx.commit().await?;
 
     Ok(())
 }
 
+/// Price history of a product, newest first
 #[tauri::command]
-pub async fn get_product(pool: State<'_, SqlitePool>, id: i64) -> Result<Product, String> {
-    let product = sqlx::query_as::<_, Product>(
-        "SELECT id, name, category, unit, current_stock, note, created_at, deleted_at 
-         FROM products 
-         WHERE id = ? AND deleted_at IS NULL",
+pub async fn list_product_prices(
+    pool: State<'_, SqlitePool>,
+    product_id: i64,
+) -> Result<Vec<ProductPrice>, AppError> {
+    let rows = sqlx::query_as::<_, ProductPrice>(
+        "SELECT id, product_id, retail_price, wholesale_price, effective_at
+         FROM product_prices
+         WHERE product_id = ?
+         ORDER BY effective_at DESC, id DESC",
     )
-    .bind(id)
-    .fetch_one(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .bind(product_id)
+    .fetch_all(&*pool)
+    .await?;
+
+    Ok(rows)
+}
+
+fn validate_prices(
+    retail_price: Option<f64>,
+    wholesale_price: Option<f64>,
+) -> Result<(), AppError> {
+    let fields: Vec<FieldError> = [
+        ("retail_price", retail_price),
+        ("wholesale_price", wholesale_price),
+    ]
+    .into_iter()
+    .filter(|(_, price)| price.is_some_and(|price| price < 0.0))
+    .map(|(field, _)| FieldError {
+        field: field.to_string(),
+        message: "售價不可為負數".to_string(),
+    })
+    .collect();
+    if !fields.is_empty() {
+        return Err(AppError::Validation {
+            message: "售價不可為負數".to_string(),
+            fields,
+        });
+    }
+
+    Ok(())
+}
+
+async fn record_price(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    product_id: i64,
+    retail_price: Option<f64>,
+    wholesale_price: Option<f64>,
+    effective_at: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO product_prices (product_id, retail_price, wholesale_price, effective_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(product_id)
+    .bind(retail_price)
+    .bind(wholesale_price)
+    .bind(effective_at)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_product(pool: State<'_, SqlitePool>, id: i64) -> Result<Product, AppError> {
+    let sql = format!("{} WHERE p.id = ? AND p.deleted_at IS NULL", PRODUCT_SELECT);
+    let product = sqlx::query_as::<_, Product>(&sql)
+        .bind(id)
+        .fetch_optional(&*pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("查無產品 id {}", id)))?;
 
     Ok(product)
 }
@@ -119,7 +236,7 @@ pub async fn remove_product(
     app: tauri::AppHandle,
     pool: State<'_, SqlitePool>,
     product: RemoveProductRequest,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let answer = app
         .dialog()
         .message("確定要刪除這個產品嗎？")
@@ -143,8 +260,7 @@ pub async fn remove_product(
     .bind(&deleted_at)
     .bind(&product.id)
     .execute(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,263 @@
+use sqlx::{SqliteConnection, SqlitePool, Transaction};
+use tauri::State;
+
+use crate::commands::formula_cmd::{quantity_from_grams, recipe_lye};
+use crate::commands::movement_cmd::{apply_material_movement, apply_product_movement};
+use crate::commands::recipe_cmd::fetch_recipe;
+use crate::models::movement::{ActionType, CreateMovementRequest, ItemType, MovementLink};
+use crate::models::production::{ProductionBatch, RunProductionBatchRequest};
+
+const DEFAULT_CURE_DAYS: i64 = 28;
+
+const BATCH_SELECT: &str = "
+    SELECT pb.id, pb.product_id, p.name as product_name, pb.recipe_id, r.name as recipe_name,
+           pb.batch_count, pb.quantity, pb.produced_at,
+           pb.cure_started_at, pb.cure_ready_at, pb.released_at, pb.lot_code, pb.note
+    FROM production_batches pb
+    JOIN products p ON pb.product_id = p.id
+    LEFT JOIN recipes r ON pb.recipe_id = r.id";
+
+#[tauri::command]
+pub async fn list_production_batches(
+    pool: State<'_, SqlitePool>,
+    product_id: Option<i64>,
+) -> Result<Vec<ProductionBatch>, String> {
+    let sql = format!(
+        "{} WHERE ? IS NULL OR pb.product_id = ? ORDER BY pb.produced_at DESC",
+        BATCH_SELECT
+    );
+    let rows = sqlx::query_as::<_, ProductionBatch>(&sql)
+        .bind(product_id)
+        .bind(product_id)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Lists batches that are still curing, the ones ready to release first
+#[tauri::command]
+pub async fn list_curing_batches(
+    pool: State<'_, SqlitePool>,
+) -> Result<Vec<ProductionBatch>, String> {
+    let sql = format!(
+        "{} WHERE pb.released_at IS NULL ORDER BY pb.cure_ready_at",
+        BATCH_SELECT
+    );
+    let rows = sqlx::query_as::<_, ProductionBatch>(&sql)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Marks a cured batch as released so its units count as sellable stock.
+/// Releasing before the cure-ready date requires `force`.
+#[tauri::command]
+pub async fn release_cured_batch(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    force: Option<bool>,
+) -> Result<ProductionBatch, String> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let batch = fetch_batch(&mut tx, id).await?;
+    if batch.released_at.is_some() {
+        return Err(format!("生產批次 #{} 已經釋出", id));
+    }
+
+    let now = chrono::Utc::now();
+    let ready = batch
+        .cure_ready_at
+        .as_deref()
+        .and_then(|ready_at| chrono::DateTime::parse_from_rfc3339(ready_at).ok())
+        .map_or(true, |ready_at| ready_at <= now);
+    if !ready && !force.unwrap_or(false) {
+        return Err(format!(
+            "生產批次 #{} 尚未熟成完成 (預計 {})",
+            id,
+            batch.cure_ready_at.unwrap_or_default()
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE production_batches
+        SET released_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(now.to_rfc3339())
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let batch = fetch_batch(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(batch)
+}
+
+/// Runs a production batch: consumes every recipe material, adds the finished
+/// products and records the batch, all in one transaction.
+#[tauri::command]
+pub async fn run_production_batch(
+    pool: State<'_, SqlitePool>,
+    req: RunProductionBatchRequest,
+) -> Result<ProductionBatch, String> {
+    if req.batch_count <= 0 {
+        return Err("批數必須大於 0".to_string());
+    }
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let recipe = fetch_recipe(&mut tx, req.recipe_id).await?;
+    if recipe.recipe.product_id != req.product_id {
+        return Err(format!(
+            "配方「{}」不屬於產品 id {}",
+            recipe.recipe.name, req.product_id
+        ));
+    }
+
+    let quantity = req
+        .quantity
+        .unwrap_or(recipe.recipe.yield_quantity * req.batch_count);
+    if quantity <= 0 {
+        return Err("生產數量必須大於 0".to_string());
+    }
+
+    let cure_days = req.cure_days.unwrap_or(DEFAULT_CURE_DAYS);
+    if cure_days < 0 {
+        return Err("熟成天數不可為負數".to_string());
+    }
+
+    let now = chrono::Utc::now();
+    let produced_at = now.to_rfc3339();
+    let cure_ready_at = (now + chrono::Duration::days(cure_days)).to_rfc3339();
+    // Batches that need no curing are sellable right away
+    let released_at = (cure_days == 0).then(|| produced_at.clone());
+
+    let batch_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO production_batches
+        (product_id, recipe_id, batch_count, quantity, remaining_quantity, produced_at, cure_started_at, cure_ready_at, released_at, note)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(req.product_id)
+    .bind(req.recipe_id)
+    .bind(req.batch_count)
+    .bind(quantity)
+    .bind(quantity)
+    .bind(&produced_at)
+    .bind(&produced_at)
+    .bind(&cure_ready_at)
+    .bind(&released_at)
+    .bind(&req.note)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let lot_code = format!("B{}-{:04}", now.format("%Y%m%d"), batch_id);
+    sqlx::query("UPDATE production_batches SET lot_code = ? WHERE id = ?")
+        .bind(&lot_code)
+        .bind(batch_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let link = MovementLink {
+        batch_id: Some(batch_id),
+        ..Default::default()
+    };
+    let note = Some(format!(
+        "生產批次 {} {}",
+        lot_code, recipe.recipe.product_name
+    ));
+
+    // Lye is deducted by the calculated amount rather than a fixed recipe line
+    let lye = recipe_lye(&mut tx, &recipe).await?;
+    let lye_material_id = lye.as_ref().map(|(material_id, _)| *material_id);
+
+    // 1. Consume every material named by the recipe
+    let mut stock_warnings = Vec::new();
+    for line in recipe
+        .lines
+        .iter()
+        .filter(|line| Some(line.material_id) != lye_material_id)
+    {
+        let consume = CreateMovementRequest {
+            note: note.clone(),
+            ..CreateMovementRequest::new(
+                ItemType::Material,
+                line.material_id,
+                ActionType::Out,
+                line.quantity * req.batch_count as f64,
+            )
+        };
+        let outcome = apply_material_movement(&mut tx, &consume, link).await?;
+        stock_warnings.extend(outcome.warning);
+    }
+
+    if let Some((material_id, result)) = &lye {
+        // The calculated lye is in grams; stock is kept in the lye material's own unit
+        let (lye_name, unit, density): (String, String, Option<f64>) =
+            sqlx::query_as("SELECT name, unit, density FROM materials WHERE id = ?")
+                .bind(material_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        let quantity =
+            quantity_from_grams(result.lye_weight * req.batch_count as f64, &unit, density)
+                .ok_or_else(|| format!("鹼「{}」以 {} 記錄，無法換算為重量", lye_name, unit))?;
+
+        let consume = CreateMovementRequest {
+            note: note.clone(),
+            ..CreateMovementRequest::new(
+                ItemType::Material,
+                *material_id,
+                ActionType::Out,
+                quantity,
+            )
+        };
+        let outcome = apply_material_movement(&mut tx, &consume, link).await?;
+        stock_warnings.extend(outcome.warning);
+    }
+
+    // 2. Add the finished products
+    let produce = CreateMovementRequest {
+        note,
+        ..CreateMovementRequest::new(
+            ItemType::Product,
+            req.product_id,
+            ActionType::Production,
+            quantity as f64,
+        )
+    };
+    apply_product_movement(&mut tx, &produce, link).await?;
+
+    let mut batch = fetch_batch(&mut tx, batch_id).await?;
+    batch.stock_warnings = stock_warnings;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(batch)
+}
+
+pub(crate) async fn fetch_batch(
+    conn: &mut SqliteConnection,
+    id: i64,
+) -> Result<ProductionBatch, String> {
+    let sql = format!("{} WHERE pb.id = ?", BATCH_SELECT);
+    sqlx::query_as::<_, ProductionBatch>(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("查無生產批次 id {}", id))
+}
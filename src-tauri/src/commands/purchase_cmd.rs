@@ -0,0 +1,424 @@
+use std::collections::HashSet;
+
+use sqlx::{SqliteConnection, SqlitePool, Transaction};
+use tauri::State;
+
+use crate::commands::movement_cmd::apply_material_movement;
+use crate::models::movement::{ActionType, CreateMovementRequest, ItemType, MovementLink};
+use crate::models::purchase::{
+    CreatePurchaseOrderRequest, PurchaseOrder, PurchaseOrderDetail, PurchaseOrderLine,
+    PurchaseOrderLineRequest, PurchaseOrderStatus, ReceivePurchaseOrderRequest,
+    UpdatePurchaseOrderRequest,
+};
+
+/// Tolerance when comparing received against ordered quantities
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+const PURCHASE_ORDER_SELECT: &str = "
+    SELECT po.id, po.supplier_id, s.name as supplier_name, po.status,
+           po.ordered_at, po.expected_at, po.received_at, po.cancelled_at,
+           COALESCE((SELECT SUM(pol.quantity * pol.unit_cost) FROM purchase_order_lines pol
+                     WHERE pol.purchase_order_id = po.id AND pol.unit_cost IS NOT NULL), 0.0) as total_cost,
+           po.note, po.created_at
+    FROM purchase_orders po
+    JOIN suppliers s ON po.supplier_id = s.id";
+
+#[tauri::command]
+pub async fn list_purchase_orders(
+    pool: State<'_, SqlitePool>,
+    status: Option<PurchaseOrderStatus>,
+) -> Result<Vec<PurchaseOrder>, String> {
+    let sql = format!(
+        "{} WHERE ? IS NULL OR po.status = ? ORDER BY po.created_at DESC",
+        PURCHASE_ORDER_SELECT
+    );
+    let rows = sqlx::query_as::<_, PurchaseOrder>(&sql)
+        .bind(status)
+        .bind(status)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn get_purchase_order(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<PurchaseOrderDetail, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    fetch_purchase_order(&mut conn, id).await
+}
+
+#[tauri::command]
+pub async fn create_purchase_order(
+    pool: State<'_, SqlitePool>,
+    order: CreatePurchaseOrderRequest,
+) -> Result<PurchaseOrderDetail, String> {
+    validate_lines(&order.lines)?;
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    ensure_supplier(&mut tx, order.supplier_id).await?;
+
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    let order_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO purchase_orders
+        (supplier_id, status, expected_at, note, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(order.supplier_id)
+    .bind(PurchaseOrderStatus::Draft)
+    .bind(&order.expected_at)
+    .bind(&order.note)
+    .bind(&created_at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    insert_order_lines(&mut tx, order_id, &order.lines).await?;
+
+    let detail = fetch_purchase_order(&mut tx, order_id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Replaces the supplier, dates and lines of a purchase order still in draft
+#[tauri::command]
+pub async fn update_purchase_order(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    req: UpdatePurchaseOrderRequest,
+) -> Result<PurchaseOrderDetail, String> {
+    validate_lines(&req.lines)?;
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let current = fetch_purchase_order(&mut tx, id).await?;
+    if current.order.status != PurchaseOrderStatus::Draft {
+        return Err(format!("採購單 #{} 已送出，不能再修改", id));
+    }
+
+    ensure_supplier(&mut tx, req.supplier_id).await?;
+
+    sqlx::query(
+        r#"
+        UPDATE purchase_orders
+        SET supplier_id = ?, expected_at = ?, note = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(req.supplier_id)
+    .bind(&req.expected_at)
+    .bind(&req.note)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query("DELETE FROM purchase_order_lines WHERE purchase_order_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    insert_order_lines(&mut tx, id, &req.lines).await?;
+
+    let detail = fetch_purchase_order(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Sends a draft purchase order to the supplier
+#[tauri::command]
+pub async fn submit_purchase_order(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<PurchaseOrderDetail, String> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let current = fetch_purchase_order(&mut tx, id).await?;
+    if current.order.status != PurchaseOrderStatus::Draft {
+        return Err(format!("只有草稿狀態的採購單可以送出 (採購單 #{})", id));
+    }
+
+    sqlx::query("UPDATE purchase_orders SET status = ?, ordered_at = ? WHERE id = ?")
+        .bind(PurchaseOrderStatus::Ordered)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let detail = fetch_purchase_order(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Cancels a purchase order. Stock already received stays in inventory; only the
+/// open remainder is dropped.
+#[tauri::command]
+pub async fn cancel_purchase_order(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<PurchaseOrderDetail, String> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let current = fetch_purchase_order(&mut tx, id).await?;
+    if matches!(
+        current.order.status,
+        PurchaseOrderStatus::Received | PurchaseOrderStatus::Cancelled
+    ) {
+        return Err(format!("採購單 #{} 已結案，無法取消", id));
+    }
+
+    sqlx::query("UPDATE purchase_orders SET status = ?, cancelled_at = ? WHERE id = ?")
+        .bind(PurchaseOrderStatus::Cancelled)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let detail = fetch_purchase_order(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Receives delivered goods against an ordered purchase order. Every line posts a
+/// material `in` movement with its unit cost; lines not fully delivered stay open.
+#[tauri::command]
+pub async fn receive_purchase_order(
+    pool: State<'_, SqlitePool>,
+    req: ReceivePurchaseOrderRequest,
+) -> Result<PurchaseOrderDetail, String> {
+    if req.lines.is_empty() {
+        return Err("請至少輸入一項到貨數量".to_string());
+    }
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let id = req.purchase_order_id;
+    let current = fetch_purchase_order(&mut tx, id).await?;
+    if !matches!(
+        current.order.status,
+        PurchaseOrderStatus::Ordered | PurchaseOrderStatus::PartiallyReceived
+    ) {
+        return Err(format!("採購單 #{} 尚未送出或已結案，無法收貨", id));
+    }
+
+    let link = MovementLink {
+        purchase_order_id: Some(id),
+        ..Default::default()
+    };
+    let note = req
+        .note
+        .clone()
+        .or_else(|| Some(format!("採購單 #{} {}", id, current.order.supplier_name)));
+
+    let mut seen = HashSet::new();
+    for receipt in &req.lines {
+        if !seen.insert(receipt.line_id) {
+            return Err(format!("採購明細 id {} 重複收貨", receipt.line_id));
+        }
+
+        let line = current
+            .lines
+            .iter()
+            .find(|line| line.id == receipt.line_id)
+            .ok_or_else(|| format!("採購單 #{} 沒有明細 id {}", id, receipt.line_id))?;
+
+        if receipt.quantity <= 0.0 {
+            return Err(format!("「{}」的到貨數量必須大於 0", line.material_name));
+        }
+        let open = line.quantity - line.received_quantity;
+        if receipt.quantity > open + QUANTITY_EPSILON {
+            return Err(format!(
+                "「{}」到貨數量 {} 超過未交數量 {}",
+                line.material_name, receipt.quantity, open
+            ));
+        }
+
+        let unit_cost = receipt
+            .unit_cost
+            .or(line.unit_cost)
+            .ok_or_else(|| format!("「{}」尚未設定單位成本", line.material_name))?;
+        if unit_cost < 0.0 {
+            return Err(format!("「{}」的單位成本不可為負數", line.material_name));
+        }
+
+        let receive = CreateMovementRequest {
+            item_type: ItemType::Material,
+            item_id: line.material_id,
+            action_type: ActionType::In,
+            change_amount: receipt.quantity,
+            note: note.clone(),
+            lot_code: receipt.lot_code.clone(),
+            received_at: req.received_at.clone(),
+            expires_at: receipt.expires_at.clone(),
+            unit_cost: Some(unit_cost),
+        };
+        apply_material_movement(&mut tx, &receive, link).await?;
+
+        sqlx::query(
+            "UPDATE purchase_order_lines SET received_quantity = received_quantity + ? WHERE id = ?",
+        )
+        .bind(receipt.quantity)
+        .bind(line.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    let open_lines: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM purchase_order_lines
+         WHERE purchase_order_id = ? AND received_quantity < quantity - ?",
+    )
+    .bind(id)
+    .bind(QUANTITY_EPSILON)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if open_lines == 0 {
+        sqlx::query("UPDATE purchase_orders SET status = ?, received_at = ? WHERE id = ?")
+            .bind(PurchaseOrderStatus::Received)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    } else {
+        sqlx::query("UPDATE purchase_orders SET status = ? WHERE id = ?")
+            .bind(PurchaseOrderStatus::PartiallyReceived)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let detail = fetch_purchase_order(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Loads a purchase order and its lines, usable from inside a transaction
+pub(crate) async fn fetch_purchase_order(
+    conn: &mut SqliteConnection,
+    id: i64,
+) -> Result<PurchaseOrderDetail, String> {
+    let sql = format!("{} WHERE po.id = ?", PURCHASE_ORDER_SELECT);
+    let order = sqlx::query_as::<_, PurchaseOrder>(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("查無採購單 id {}", id))?;
+
+    let lines = sqlx::query_as::<_, PurchaseOrderLine>(
+        "SELECT pol.id, pol.purchase_order_id, pol.material_id, m.name as material_name,
+                m.unit as material_unit, pol.quantity, pol.received_quantity, pol.unit_cost
+         FROM purchase_order_lines pol
+         JOIN materials m ON pol.material_id = m.id
+         WHERE pol.purchase_order_id = ?
+         ORDER BY pol.id",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(PurchaseOrderDetail { order, lines })
+}
+
+fn validate_lines(lines: &[PurchaseOrderLineRequest]) -> Result<(), String> {
+    if lines.is_empty() {
+        return Err("採購單至少需要一項原料".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    for line in lines {
+        if line.quantity <= 0.0 {
+            return Err(format!("原料 id {} 的採購數量必須大於 0", line.material_id));
+        }
+        if line.unit_cost.is_some_and(|cost| cost < 0.0) {
+            return Err(format!("原料 id {} 的單位成本不可為負數", line.material_id));
+        }
+        if !seen.insert(line.material_id) {
+            return Err(format!("原料 id {} 在採購單中重複", line.material_id));
+        }
+    }
+
+    Ok(())
+}
+
+async fn ensure_supplier(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    supplier_id: i64,
+) -> Result<(), String> {
+    let supplier_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM suppliers WHERE id = ? AND deleted_at IS NULL)",
+    )
+    .bind(supplier_id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if !supplier_exists {
+        return Err(format!("查無供應商 id {}", supplier_id));
+    }
+
+    Ok(())
+}
+
+async fn insert_order_lines(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    order_id: i64,
+    lines: &[PurchaseOrderLineRequest],
+) -> Result<(), String> {
+    for line in lines {
+        let material_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM materials WHERE id = ? AND deleted_at IS NULL)",
+        )
+        .bind(line.material_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if !material_exists {
+            return Err(format!("查無原料 id {}", line.material_id));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO purchase_order_lines (purchase_order_id, material_id, quantity, unit_cost)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(order_id)
+        .bind(line.material_id)
+        .bind(line.quantity)
+        .bind(line.unit_cost)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+
+use sqlx::{SqliteConnection, SqlitePool, Transaction};
+use tauri::State;
+
+use crate::models::recipe::{
+    CreateRecipeRequest, Recipe, RecipeDetail, RecipeLine, RecipeLineRequest, RecipeLye,
+    UpdateRecipeRequest,
+};
+
+#[tauri::command]
+pub async fn list_recipes(
+    pool: State<'_, SqlitePool>,
+    product_id: Option<i64>,
+) -> Result<Vec<Recipe>, String> {
+    let rows = sqlx::query_as::<_, Recipe>(
+        "SELECT r.id, r.product_id, p.name as product_name, r.name, r.yield_quantity,
+                r.lye_material_id, r.lye_type, r.superfat, r.water_ratio, r.lye_concentration,
+                r.note, r.created_at
+         FROM recipes r
+         JOIN products p ON r.product_id = p.id
+         WHERE p.deleted_at IS NULL AND (? IS NULL OR r.product_id = ?)
+         ORDER BY p.name, r.name",
+    )
+    .bind(product_id)
+    .bind(product_id)
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn get_recipe(pool: State<'_, SqlitePool>, id: i64) -> Result<RecipeDetail, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    fetch_recipe(&mut conn, id).await
+}
+
+#[tauri::command]
+pub async fn create_recipe(
+    pool: State<'_, SqlitePool>,
+    recipe: CreateRecipeRequest,
+) -> Result<(), String> {
+    validate_recipe(&recipe.name, recipe.yield_quantity, &recipe.lines)?;
+    validate_lye(&recipe.lye)?;
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let product_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM products WHERE id = ? AND deleted_at IS NULL)",
+    )
+    .bind(recipe.product_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if !product_exists {
+        return Err(format!("查無產品 id {}", recipe.product_id));
+    }
+
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    let recipe_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO recipes
+        (product_id, name, yield_quantity, lye_material_id, lye_type, superfat, water_ratio, lye_concentration, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(recipe.product_id)
+    .bind(&recipe.name)
+    .bind(recipe.yield_quantity)
+    .bind(recipe.lye.lye_material_id)
+    .bind(recipe.lye.lye_type)
+    .bind(recipe.lye.superfat)
+    .bind(recipe.lye.water_ratio)
+    .bind(recipe.lye.lye_concentration)
+    .bind(&recipe.note)
+    .bind(&created_at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    insert_recipe_lines(&mut tx, recipe_id, &recipe.lines).await?;
+    ensure_lye_material(&mut tx, &recipe.lye).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_recipe(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    req: UpdateRecipeRequest,
+) -> Result<(), String> {
+    validate_recipe(&req.name, req.yield_quantity, &req.lines)?;
+    validate_lye(&req.lye)?;
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE recipes
+        SET name = ?, yield_quantity = ?, lye_material_id = ?, lye_type = ?, superfat = ?,
+            water_ratio = ?, lye_concentration = ?, note = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&req.name)
+    .bind(req.yield_quantity)
+    .bind(req.lye.lye_material_id)
+    .bind(req.lye.lye_type)
+    .bind(req.lye.superfat)
+    .bind(req.lye.water_ratio)
+    .bind(req.lye.lye_concentration)
+    .bind(&req.note)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("查無配方 id {}", id));
+    }
+
+    // Lines are replaced as a whole so the recipe always matches what was submitted
+    sqlx::query("DELETE FROM recipe_lines WHERE recipe_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    insert_recipe_lines(&mut tx, id, &req.lines).await?;
+    ensure_lye_material(&mut tx, &req.lye).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Loads a recipe and its lines, usable from inside a transaction
+pub(crate) async fn fetch_recipe(
+    conn: &mut SqliteConnection,
+    id: i64,
+) -> Result<RecipeDetail, String> {
+    let recipe = sqlx::query_as::<_, Recipe>(
+        "SELECT r.id, r.product_id, p.name as product_name, r.name, r.yield_quantity,
+                r.lye_material_id, r.lye_type, r.superfat, r.water_ratio, r.lye_concentration,
+                r.note, r.created_at
+         FROM recipes r
+         JOIN products p ON r.product_id = p.id
+         WHERE r.id = ?",
+    )
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("查無配方 id {}", id))?;
+
+    let lines = sqlx::query_as::<_, RecipeLine>(
+        "SELECT rl.id, rl.recipe_id, rl.material_id, m.name as material_name,
+                COALESCE(m.category, '') as material_category, m.unit as material_unit,
+                m.density as material_density, rl.quantity
+         FROM recipe_lines rl
+         JOIN materials m ON rl.material_id = m.id
+         WHERE rl.recipe_id = ?
+         ORDER BY rl.id",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(RecipeDetail { recipe, lines })
+}
+
+fn validate_recipe(
+    name: &str,
+    yield_quantity: i64,
+    lines: &[RecipeLineRequest],
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("配方名稱不可為空".to_string());
+    }
+    if yield_quantity <= 0 {
+        return Err("每批產量必須大於 0".to_string());
+    }
+    if lines.is_empty() {
+        return Err("配方至少需要一項原料".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    for line in lines {
+        if line.quantity <= 0.0 {
+            return Err(format!("原料 id {} 的用量必須大於 0", line.material_id));
+        }
+        if !seen.insert(line.material_id) {
+            return Err(format!("原料 id {} 在配方中重複", line.material_id));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_lye(lye: &RecipeLye) -> Result<(), String> {
+    if lye.lye_material_id.is_none() {
+        return Ok(());
+    }
+
+    if lye.lye_type.is_none() {
+        return Err("請指定鹼的種類 (NaOH / KOH)".to_string());
+    }
+    if let Some(superfat) = lye.superfat {
+        if !(0.0..100.0).contains(&superfat) {
+            return Err("超脂比例必須介於 0 到 100 之間".to_string());
+        }
+    }
+    match (lye.water_ratio, lye.lye_concentration) {
+        (Some(ratio), None) if ratio > 0.0 => Ok(()),
+        (None, Some(concentration)) if concentration > 0.0 && concentration < 100.0 => Ok(()),
+        _ => Err("請指定有效的水鹼比或鹼液濃度其中之一".to_string()),
+    }
+}
+
+async fn ensure_lye_material(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    lye: &RecipeLye,
+) -> Result<(), String> {
+    let Some(material_id) = lye.lye_material_id else {
+        return Ok(());
+    };
+
+    let material_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM materials WHERE id = ? AND deleted_at IS NULL)",
+    )
+    .bind(material_id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if !material_exists {
+        return Err(format!("查無原料 id {}", material_id));
+    }
+
+    Ok(())
+}
+
+async fn insert_recipe_lines(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    recipe_id: i64,
+    lines: &[RecipeLineRequest],
+) -> Result<(), String> {
+    for line in lines {
+        let material_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM materials WHERE id = ? AND deleted_at IS NULL)",
+        )
+        .bind(line.material_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if !material_exists {
+            return Err(format!("查無原料 id {}", line.material_id));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO recipe_lines (recipe_id, material_id, quantity)
+            VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(recipe_id)
+        .bind(line.material_id)
+        .bind(line.quantity)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
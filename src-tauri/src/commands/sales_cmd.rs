@@ -0,0 +1,584 @@
+use std::collections::HashSet;
+
+use sqlx::{SqliteConnection, SqlitePool, Transaction};
+use tauri::State;
+use tauri_plugin_dialog::{DialogExt, FilePath};
+
+use crate::commands::movement_cmd::{apply_product_movement, restore_batches};
+use crate::commands::settings_cmd::{
+    read_integer_setting, read_setting, write_setting, INVOICE_NEXT_NUMBER, INVOICE_NUMBER_DIGITS,
+    INVOICE_PREFIX, WORKSHOP_ADDRESS, WORKSHOP_EMAIL, WORKSHOP_NAME, WORKSHOP_PHONE,
+    WORKSHOP_TAX_ID,
+};
+use crate::models::customer::{Customer, PriceTier};
+use crate::models::movement::{ActionType, CreateMovementRequest, ItemType, MovementLink};
+use crate::models::sales::{
+    CreateSalesOrderRequest, ReturnCondition, ReturnSalesItemsRequest, SalesItem, SalesOrder,
+    SalesOrderDetail, SalesOrderStatus, SalesReturn,
+};
+use crate::pdf::invoice::{self, WorkshopDetails};
+
+const SALES_ORDER_SELECT: &str = "
+    SELECT so.id, so.customer_id, c.name as customer_name, so.order_date,
+           COALESCE(so.total_amount, 0.0) as total_amount, so.status, so.refund_amount,
+           so.cancelled_at, so.invoice_number, so.note, so.created_at
+    FROM sales_orders so
+    LEFT JOIN customers c ON so.customer_id = c.id";
+
+#[tauri::command]
+pub async fn list_sales_orders(
+    pool: State<'_, SqlitePool>,
+    customer_id: Option<i64>,
+) -> Result<Vec<SalesOrder>, String> {
+    let sql = format!(
+        "{} WHERE ? IS NULL OR so.customer_id = ? ORDER BY so.order_date DESC, so.id DESC",
+        SALES_ORDER_SELECT
+    );
+    let rows = sqlx::query_as::<_, SalesOrder>(&sql)
+        .bind(customer_id)
+        .bind(customer_id)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn get_sales_order(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<SalesOrderDetail, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    fetch_sales_order(&mut conn, id).await
+}
+
+/// Records a sales order and deducts every item from product stock with a `sale`
+/// movement, all in one transaction. Items that would drive stock negative follow
+/// the negative-stock policy: `reject` fails the whole order, `warn` records it and
+/// returns each shortage in `stock_warnings`, and `allow` records it silently.
+#[tauri::command]
+pub async fn create_sales_order(
+    pool: State<'_, SqlitePool>,
+    order: CreateSalesOrderRequest,
+) -> Result<SalesOrderDetail, String> {
+    if order.items.is_empty() {
+        return Err("銷售單至少需要一項產品".to_string());
+    }
+    for item in &order.items {
+        if item.quantity <= 0 {
+            return Err(format!("產品 id {} 的數量必須大於 0", item.product_id));
+        }
+        if item.price.is_some_and(|price| price < 0.0) {
+            return Err(format!("產品 id {} 的單價不可為負數", item.product_id));
+        }
+    }
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    // Walk-in customers pay retail
+    let (customer_name, price_tier) = match order.customer_id {
+        Some(customer_id) => {
+            let customer: Option<(String, PriceTier)> = sqlx::query_as(
+                "SELECT name, price_tier FROM customers WHERE id = ? AND deleted_at IS NULL",
+            )
+            .bind(customer_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            let (name, tier) = customer.ok_or_else(|| format!("查無客戶 id {}", customer_id))?;
+            (Some(name), tier)
+        }
+        None => (None, PriceTier::Retail),
+    };
+
+    let mut prices = Vec::with_capacity(order.items.len());
+    for item in &order.items {
+        let product: Option<(String, Option<f64>, Option<f64>)> = sqlx::query_as(
+            "SELECT name, retail_price, wholesale_price FROM products
+             WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(item.product_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        let (name, retail_price, wholesale_price) =
+            product.ok_or_else(|| format!("查無產品 id {}", item.product_id))?;
+
+        // Wholesale customers fall back to retail when no wholesale price is set
+        let tier_price = match price_tier {
+            PriceTier::Retail => retail_price,
+            PriceTier::Wholesale => wholesale_price.or(retail_price),
+        };
+        let price = item
+            .price
+            .or(tier_price)
+            .ok_or_else(|| format!("「{}」尚未設定售價，請輸入單價", name))?;
+        prices.push(price);
+    }
+
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let order_date = order
+        .order_date
+        .clone()
+        .unwrap_or_else(|| created_at.clone());
+    let total_amount: f64 = order
+        .items
+        .iter()
+        .zip(&prices)
+        .map(|(item, price)| item.quantity as f64 * price)
+        .sum();
+
+    let order_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO sales_orders
+        (customer_id, order_date, total_amount, status, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(order.customer_id)
+    .bind(&order_date)
+    .bind(total_amount)
+    .bind(SalesOrderStatus::Completed)
+    .bind(&order.note)
+    .bind(&created_at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let link = MovementLink {
+        sales_order_id: Some(order_id),
+        ..Default::default()
+    };
+    let note = Some(match &customer_name {
+        Some(name) => format!("銷售單 #{} {}", order_id, name),
+        None => format!("銷售單 #{}", order_id),
+    });
+
+    let mut stock_warnings = Vec::new();
+    for (item, price) in order.items.iter().zip(&prices) {
+        let sale = CreateMovementRequest {
+            note: note.clone(),
+            ..CreateMovementRequest::new(
+                ItemType::Product,
+                item.product_id,
+                ActionType::Sale,
+                item.quantity as f64,
+            )
+        };
+        let outcome = apply_product_movement(&mut tx, &sale, link).await?;
+        stock_warnings.extend(outcome.warning);
+
+        sqlx::query(
+            r#"
+            INSERT INTO sales_items (order_id, product_id, quantity, price, inventory_log_id)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(order_id)
+        .bind(item.product_id)
+        .bind(item.quantity)
+        .bind(price)
+        .bind(outcome.log_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    let mut detail = fetch_sales_order(&mut tx, order_id).await?;
+    detail.stock_warnings = stock_warnings;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Cancels a sales order: every bar not yet returned goes back to sellable stock
+/// with a compensating movement and is refunded at its sale price.
+#[tauri::command]
+pub async fn cancel_sales_order(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    note: Option<String>,
+) -> Result<SalesOrderDetail, String> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let current = fetch_sales_order(&mut tx, id).await?;
+    match current.order.status {
+        SalesOrderStatus::Cancelled => return Err(format!("銷售單 #{} 已經取消", id)),
+        SalesOrderStatus::Returned => return Err(format!("銷售單 #{} 已全數退貨", id)),
+        _ => {}
+    }
+
+    let note = note.or_else(|| Some(format!("取消銷售單 #{}", id)));
+    for item in &current.items {
+        let quantity = item.quantity - item.returned_quantity;
+        if quantity > 0 {
+            let refund_amount = quantity as f64 * item.price;
+            return_item(
+                &mut tx,
+                item,
+                quantity,
+                ReturnCondition::Sellable,
+                refund_amount,
+                &note,
+            )
+            .await?;
+        }
+    }
+
+    sqlx::query("UPDATE sales_orders SET status = ?, cancelled_at = ? WHERE id = ?")
+        .bind(SalesOrderStatus::Cancelled)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let detail = fetch_sales_order(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Accepts returned bars against a sales order. Sellable bars go back to stock,
+/// damaged ones into the product's quarantine stock; both are written as
+/// compensating movements linked to the original sale.
+#[tauri::command]
+pub async fn return_sales_items(
+    pool: State<'_, SqlitePool>,
+    req: ReturnSalesItemsRequest,
+) -> Result<SalesOrderDetail, String> {
+    if req.items.is_empty() {
+        return Err("請至少選擇一項退貨".to_string());
+    }
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let id = req.order_id;
+    let current = fetch_sales_order(&mut tx, id).await?;
+    if current.order.status == SalesOrderStatus::Cancelled {
+        return Err(format!("銷售單 #{} 已經取消", id));
+    }
+
+    let note = req
+        .note
+        .clone()
+        .or_else(|| Some(format!("銷售單 #{} 退貨", id)));
+
+    let mut seen = HashSet::new();
+    for returned in &req.items {
+        if !seen.insert(returned.sales_item_id) {
+            return Err(format!("銷售明細 id {} 重複退貨", returned.sales_item_id));
+        }
+
+        let item = current
+            .items
+            .iter()
+            .find(|item| item.id == returned.sales_item_id)
+            .ok_or_else(|| format!("銷售單 #{} 沒有明細 id {}", id, returned.sales_item_id))?;
+
+        let returnable = item.quantity - item.returned_quantity;
+        if returned.quantity <= 0 || returned.quantity > returnable {
+            return Err(format!(
+                "「{}」退貨數量必須介於 1 到 {} 之間",
+                item.product_name, returnable
+            ));
+        }
+
+        let refund_amount = returned
+            .refund_amount
+            .unwrap_or(returned.quantity as f64 * item.price);
+        if refund_amount < 0.0 {
+            return Err("退款金額不可為負數".to_string());
+        }
+
+        return_item(
+            &mut tx,
+            item,
+            returned.quantity,
+            returned.condition,
+            refund_amount,
+            &note,
+        )
+        .await?;
+    }
+
+    let fully_returned: bool = sqlx::query_scalar(
+        "SELECT NOT EXISTS(SELECT 1 FROM sales_items WHERE order_id = ? AND returned_quantity < quantity)",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let status = if fully_returned {
+        SalesOrderStatus::Returned
+    } else {
+        SalesOrderStatus::PartiallyReturned
+    };
+    sqlx::query("UPDATE sales_orders SET status = ? WHERE id = ?")
+        .bind(status)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let detail = fetch_sales_order(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Writes the compensating movements for returned bars of one sales item and
+/// records the return and its refund on the order.
+async fn return_item(
+    conn: &mut SqliteConnection,
+    item: &SalesItem,
+    quantity: i64,
+    condition: ReturnCondition,
+    refund_amount: f64,
+    note: &Option<String>,
+) -> Result<(), String> {
+    let link = MovementLink {
+        sales_order_id: Some(item.order_id),
+        reversal_of: item.inventory_log_id,
+        ..Default::default()
+    };
+
+    // 1. Reverse the sale
+    let back = CreateMovementRequest {
+        note: note.clone(),
+        ..CreateMovementRequest::new(
+            ItemType::Product,
+            item.product_id,
+            ActionType::Return,
+            quantity as f64,
+        )
+    };
+    let log_id = apply_product_movement(conn, &back, link).await?.log_id;
+
+    match condition {
+        ReturnCondition::Sellable => {
+            if let Some(sale_log_id) = item.inventory_log_id {
+                restore_batches(conn, sale_log_id, quantity, log_id).await?;
+            }
+        }
+        // 2. Damaged bars leave sellable stock again, into quarantine
+        ReturnCondition::Damaged => {
+            let quarantine = CreateMovementRequest {
+                note: Some(format!("退貨損壞 (銷售單 #{})", item.order_id)),
+                ..CreateMovementRequest::new(
+                    ItemType::Product,
+                    item.product_id,
+                    ActionType::Quarantine,
+                    quantity as f64,
+                )
+            };
+            let quarantine_link = MovementLink {
+                sales_order_id: Some(item.order_id),
+                ..Default::default()
+            };
+            apply_product_movement(conn, &quarantine, quarantine_link).await?;
+        }
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO sales_returns
+        (order_id, sales_item_id, quantity, condition, refund_amount, inventory_log_id, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(item.order_id)
+    .bind(item.id)
+    .bind(quantity)
+    .bind(condition)
+    .bind(refund_amount)
+    .bind(log_id)
+    .bind(note.as_deref())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query("UPDATE sales_items SET returned_quantity = returned_quantity + ? WHERE id = ?")
+        .bind(quantity)
+        .bind(item.id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    sqlx::query("UPDATE sales_orders SET refund_amount = refund_amount + ? WHERE id = ?")
+        .bind(refund_amount)
+        .bind(item.order_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Renders a sales order as a PDF receipt and saves it where the user picks.
+/// The first print takes the next number from the invoice sequence in
+/// `app_settings`; reprints keep that number.
+#[tauri::command]
+pub async fn generate_invoice_pdf(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    order_id: i64,
+) -> Result<String, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let order = fetch_sales_order(&mut conn, order_id).await?;
+    drop(conn);
+
+    if order.order.status == SalesOrderStatus::Cancelled {
+        return Err(format!("銷售單 #{} 已取消，無法開立收據", order_id));
+    }
+
+    let file_name = match &order.order.invoice_number {
+        Some(invoice_number) => format!("{}.pdf", invoice_number),
+        None => format!("receipt_order-{}.pdf", order_id),
+    };
+    let export_file = app
+        .dialog()
+        .file()
+        .add_filter("PDF", &["pdf"])
+        .set_file_name(&file_name)
+        .blocking_save_file();
+
+    let export_path = match export_file {
+        Some(FilePath::Path(p)) => p.display().to_string(),
+        _ => return Err("No file path selected".to_string()),
+    };
+
+    // Only take a number once the user has actually chosen to save
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let invoice_number = match &order.order.invoice_number {
+        Some(invoice_number) => invoice_number.clone(),
+        None => assign_invoice_number(&mut tx, order_id).await?,
+    };
+
+    let customer = match order.order.customer_id {
+        Some(customer_id) => sqlx::query_as::<_, Customer>(
+            "SELECT id, name, phone, email, price_tier, note, created_at, deleted_at
+             FROM customers
+             WHERE id = ?",
+        )
+        .bind(customer_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?,
+        None => None,
+    };
+
+    let workshop = WorkshopDetails {
+        name: read_setting(&mut tx, WORKSHOP_NAME).await?,
+        address: read_setting(&mut tx, WORKSHOP_ADDRESS).await?,
+        phone: read_setting(&mut tx, WORKSHOP_PHONE).await?,
+        email: read_setting(&mut tx, WORKSHOP_EMAIL).await?,
+        tax_id: read_setting(&mut tx, WORKSHOP_TAX_ID).await?,
+    };
+
+    let pdf = invoice::render(&invoice_number, &workshop, customer.as_ref(), &order);
+    std::fs::write(&export_path, pdf).map_err(|e| format!("Failed to write PDF file: {}", e))?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    app.dialog()
+        .message(format!("收據 {} 已儲存！\n{}", invoice_number, export_path))
+        .title("匯出成功")
+        .blocking_show();
+
+    Ok(export_path)
+}
+
+/// Takes the next number from the invoice sequence and stores it on the order
+async fn assign_invoice_number(
+    conn: &mut SqliteConnection,
+    order_id: i64,
+) -> Result<String, String> {
+    let prefix = read_setting(conn, INVOICE_PREFIX)
+        .await?
+        .unwrap_or_else(|| "INV-".to_string());
+    let next_number = read_integer_setting(conn, INVOICE_NEXT_NUMBER)
+        .await?
+        .unwrap_or(1);
+    let digits = read_integer_setting(conn, INVOICE_NUMBER_DIGITS)
+        .await?
+        .unwrap_or(5)
+        .clamp(1, 12) as usize;
+
+    let invoice_number = format!("{}{:0width$}", prefix, next_number, width = digits);
+
+    sqlx::query("UPDATE sales_orders SET invoice_number = ? WHERE id = ?")
+        .bind(&invoice_number)
+        .bind(order_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            if e.to_string().contains("UNIQUE") {
+                format!("收據號碼 {} 已被使用，請調整收據編號設定", invoice_number)
+            } else {
+                e.to_string()
+            }
+        })?;
+
+    write_setting(conn, INVOICE_NEXT_NUMBER, &(next_number + 1).to_string()).await?;
+
+    Ok(invoice_number)
+}
+
+/// Loads a sales order and its items, usable from inside a transaction
+pub(crate) async fn fetch_sales_order(
+    conn: &mut SqliteConnection,
+    id: i64,
+) -> Result<SalesOrderDetail, String> {
+    let sql = format!("{} WHERE so.id = ?", SALES_ORDER_SELECT);
+    let order = sqlx::query_as::<_, SalesOrder>(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("查無銷售單 id {}", id))?;
+
+    let items = sqlx::query_as::<_, SalesItem>(
+        "SELECT si.id, si.order_id, si.product_id, p.name as product_name, si.quantity,
+                si.returned_quantity, si.price, si.quantity * si.price as subtotal,
+                si.inventory_log_id
+         FROM sales_items si
+         JOIN products p ON si.product_id = p.id
+         WHERE si.order_id = ?
+         ORDER BY si.id",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let returns = sqlx::query_as::<_, SalesReturn>(
+        "SELECT sr.id, sr.order_id, sr.sales_item_id, si.product_id, p.name as product_name,
+                sr.quantity, sr.condition, sr.refund_amount, sr.inventory_log_id, sr.note,
+                sr.created_at
+         FROM sales_returns sr
+         JOIN sales_items si ON sr.sales_item_id = si.id
+         JOIN products p ON si.product_id = p.id
+         WHERE sr.order_id = ?
+         ORDER BY sr.id",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(SalesOrderDetail {
+        order,
+        items,
+        returns,
+        stock_warnings: Vec::new(),
+    })
+}
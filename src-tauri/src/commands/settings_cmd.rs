@@ -0,0 +1,118 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+
+/// Labour and overhead cost of one production batch
+pub(crate) const LABOUR_COST_PER_BATCH: &str = "labour_cost_per_batch";
+
+/// Workshop details printed on receipts
+pub(crate) const WORKSHOP_NAME: &str = "workshop_name";
+pub(crate) const WORKSHOP_ADDRESS: &str = "workshop_address";
+pub(crate) const WORKSHOP_PHONE: &str = "workshop_phone";
+pub(crate) const WORKSHOP_EMAIL: &str = "workshop_email";
+pub(crate) const WORKSHOP_TAX_ID: &str = "workshop_tax_id";
+
+/// Patterns for generated SKUs, e.g. `P{id:05}` or `SOAP-{yyyy}{mm}-{id}`
+pub(crate) const PRODUCT_SKU_PATTERN: &str = "product_sku_pattern";
+pub(crate) const MATERIAL_SKU_PATTERN: &str = "material_sku_pattern";
+
+/// `reject`, `warn` or `allow` movements that take stock below zero
+pub(crate) const MATERIAL_NEGATIVE_STOCK_POLICY: &str = "material_negative_stock_policy";
+pub(crate) const PRODUCT_NEGATIVE_STOCK_POLICY: &str = "product_negative_stock_policy";
+
+/// Months from manufacture to the expiry date printed on labels
+pub(crate) const SHELF_LIFE_MONTHS: &str = "shelf_life_months";
+
+/// Invoice number sequence: prefix, next number and zero-padded width,
+/// e.g. `INV-` / `42` / `5` gives `INV-00042`
+pub(crate) const INVOICE_PREFIX: &str = "invoice_prefix";
+pub(crate) const INVOICE_NEXT_NUMBER: &str = "invoice_next_number";
+pub(crate) const INVOICE_NUMBER_DIGITS: &str = "invoice_number_digits";
+
+#[tauri::command]
+pub async fn get_setting(
+    pool: State<'_, SqlitePool>,
+    key: String,
+) -> Result<Option<String>, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    read_setting(&mut conn, &key).await
+}
+
+#[tauri::command]
+pub async fn set_setting(
+    pool: State<'_, SqlitePool>,
+    key: String,
+    value: String,
+) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("設定名稱不可為空".to_string());
+    }
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    write_setting(&mut conn, &key, &value).await
+}
+
+pub(crate) async fn read_setting(
+    conn: &mut SqliteConnection,
+    key: &str,
+) -> Result<Option<String>, String> {
+    sqlx::query_scalar("SELECT value FROM app_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Reads a numeric setting, `None` when it has not been set
+pub(crate) async fn read_number_setting(
+    conn: &mut SqliteConnection,
+    key: &str,
+) -> Result<Option<f64>, String> {
+    read_setting(conn, key)
+        .await?
+        .map(|value| {
+            value
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("設定 {} 的值不是數字: {}", key, value))
+        })
+        .transpose()
+}
+
+/// Reads a whole-number setting, `None` when it has not been set
+pub(crate) async fn read_integer_setting(
+    conn: &mut SqliteConnection,
+    key: &str,
+) -> Result<Option<i64>, String> {
+    read_setting(conn, key)
+        .await?
+        .map(|value| {
+            value
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| format!("設定 {} 的值不是整數: {}", key, value))
+        })
+        .transpose()
+}
+
+pub(crate) async fn write_setting(
+    conn: &mut SqliteConnection,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO app_settings (key, value)
+        VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value
+        "#,
+    )
+    .bind(key)
+    .bind(value)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
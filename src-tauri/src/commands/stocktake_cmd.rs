@@ -0,0 +1,509 @@
+use rust_xlsxwriter::{Format, Workbook};
+use sqlx::{SqliteConnection, SqlitePool, Transaction};
+use tauri::State;
+use tauri_plugin_dialog::{DialogExt, FilePath};
+
+use crate::commands::movement_cmd::{apply_material_movement, apply_product_movement};
+use crate::models::movement::{ActionType, CreateMovementRequest, ItemType, MovementLink};
+use crate::models::stocktake::{
+    CreateStocktakeRequest, StocktakeCountRequest, StocktakeDetail, StocktakeLine,
+    StocktakeSession, StocktakeStatus,
+};
+use crate::pdf::count_sheet;
+
+/// Selects stocktake sessions with their count progress; append WHERE / ORDER BY clauses
+const STOCKTAKE_SELECT: &str = "
+    SELECT
+        s.*,
+        (SELECT COUNT(*) FROM stocktake_lines WHERE session_id = s.id) as line_count,
+        (SELECT COUNT(*) FROM stocktake_lines
+         WHERE session_id = s.id AND counted_quantity IS NOT NULL) as counted_count
+    FROM stocktake_sessions s";
+
+#[tauri::command]
+pub async fn list_stocktakes(pool: State<'_, SqlitePool>) -> Result<Vec<StocktakeSession>, String> {
+    let sql = format!("{} ORDER BY s.started_at DESC", STOCKTAKE_SELECT);
+    let rows = sqlx::query_as::<_, StocktakeSession>(&sql)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn get_stocktake(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<StocktakeDetail, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    fetch_stocktake(&mut conn, id).await
+}
+
+/// Starts a count of every active material and/or product, optionally limited to one category
+#[tauri::command]
+pub async fn create_stocktake(
+    pool: State<'_, SqlitePool>,
+    req: CreateStocktakeRequest,
+) -> Result<StocktakeDetail, String> {
+    if req.name.trim().is_empty() {
+        return Err("盤點名稱不可為空".to_string());
+    }
+    let item_type = req.item_type;
+    let category = req
+        .category
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO stocktake_sessions (name, item_type, category, status, note, started_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(req.name.trim())
+    .bind(item_type)
+    .bind(category)
+    .bind(StocktakeStatus::Counting)
+    .bind(req.note.as_deref())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for line_type in [ItemType::Material, ItemType::Product] {
+        if item_type.is_some_and(|t| t != line_type) {
+            continue;
+        }
+        let sql = format!(
+            "INSERT INTO stocktake_lines (session_id, item_type, item_id)
+             SELECT ?, ?, id FROM {}
+             WHERE deleted_at IS NULL AND (? IS NULL OR category = ?)",
+            line_type.table()
+        );
+        sqlx::query(&sql)
+            .bind(id)
+            .bind(line_type)
+            .bind(category)
+            .bind(category)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let detail = fetch_stocktake(&mut tx, id).await?;
+    if detail.lines.is_empty() {
+        return Err("沒有符合條件的品項可盤點".to_string());
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Records counted quantities. Counts can be entered over several sittings
+/// and changed freely until the session is posted. The system stock is noted
+/// with each count, so movements made after counting are not mistaken for variance.
+#[tauri::command]
+pub async fn record_stocktake_counts(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    counts: Vec<StocktakeCountRequest>,
+) -> Result<StocktakeDetail, String> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    ensure_counting(&mut tx, id).await?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for count in &counts {
+        let (item_type, item_id): (ItemType, i64) = sqlx::query_as(
+            "SELECT item_type, item_id FROM stocktake_lines WHERE id = ? AND session_id = ?",
+        )
+        .bind(count.line_id)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("盤點單 #{} 沒有項目 id {}", id, count.line_id))?;
+
+        if let Some(quantity) = count.counted_quantity {
+            if !quantity.is_finite() || quantity < 0.0 {
+                return Err("盤點數量不可為負數".to_string());
+            }
+            if item_type == ItemType::Product && quantity.fract() != 0.0 {
+                return Err("成品盤點數量必須是整數".to_string());
+            }
+        }
+
+        let system_stock = match count.counted_quantity {
+            Some(_) => Some(current_stock(&mut tx, item_type, item_id).await?),
+            None => None,
+        };
+
+        sqlx::query(
+            "UPDATE stocktake_lines
+             SET counted_quantity = ?, counted_at = ?, system_stock = ?, note = ?
+             WHERE id = ?",
+        )
+        .bind(count.counted_quantity)
+        .bind(count.counted_quantity.map(|_| now.as_str()))
+        .bind(system_stock)
+        .bind(count.note.as_deref())
+        .bind(count.line_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    let detail = fetch_stocktake(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Posts every variance as an `adj` movement linked to the session, all in one
+/// transaction. The variance is measured against the stock noted when the line
+/// was counted and applied on top of the stock at posting, keeping movements
+/// made in between. Every line must be counted first; items whose count
+/// matches are left untouched.
+#[tauri::command]
+pub async fn post_stocktake(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<StocktakeDetail, String> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let current = fetch_stocktake(&mut tx, id).await?;
+    ensure_counting(&mut tx, id).await?;
+
+    let uncounted = current.session.line_count - current.session.counted_count;
+    if uncounted > 0 {
+        return Err(format!("盤點單 #{} 尚有 {} 項未盤點", id, uncounted));
+    }
+
+    let link = MovementLink {
+        stocktake_session_id: Some(id),
+        ..Default::default()
+    };
+    let note = Some(format!("盤點 #{} {}", id, current.session.name));
+    for line in &current.lines {
+        let variance = line.variance.unwrap_or(0.0);
+
+        let mut log_id = None;
+        if variance.abs() > f64::EPSILON {
+            let stock = current_stock(&mut tx, line.item_type, line.item_id).await?;
+            let req = CreateMovementRequest {
+                note: line
+                    .note
+                    .clone()
+                    .filter(|n| !n.is_empty())
+                    .or_else(|| note.clone()),
+                ..CreateMovementRequest::new(
+                    line.item_type,
+                    line.item_id,
+                    ActionType::Adj,
+                    stock + variance,
+                )
+            };
+            log_id = Some(if line.item_type == ItemType::Material {
+                apply_material_movement(&mut tx, &req, link).await?.log_id
+            } else {
+                apply_product_movement(&mut tx, &req, link).await?.log_id
+            });
+        }
+
+        sqlx::query("UPDATE stocktake_lines SET inventory_log_id = ? WHERE id = ?")
+            .bind(log_id)
+            .bind(line.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    sqlx::query("UPDATE stocktake_sessions SET status = ?, posted_at = ? WHERE id = ?")
+        .bind(StocktakeStatus::Posted)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let detail = fetch_stocktake(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Abandons a session without touching stock
+#[tauri::command]
+pub async fn cancel_stocktake(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<StocktakeDetail, String> {
+    let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await.map_err(|e| e.to_string())?;
+
+    ensure_counting(&mut tx, id).await?;
+
+    sqlx::query("UPDATE stocktake_sessions SET status = ?, cancelled_at = ? WHERE id = ?")
+        .bind(StocktakeStatus::Cancelled)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let detail = fetch_stocktake(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(detail)
+}
+
+/// Saves a printable count sheet for the session as a PDF
+#[tauri::command]
+pub async fn export_stocktake_sheet(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<String, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let detail = fetch_stocktake(&mut conn, id).await?;
+    drop(conn);
+
+    let export_file = app
+        .dialog()
+        .file()
+        .add_filter("PDF", &["pdf"])
+        .set_file_name(format!("stocktake_{}.pdf", id))
+        .blocking_save_file();
+
+    let export_path = match export_file {
+        Some(FilePath::Path(p)) => p.display().to_string(),
+        _ => return Err("No file path selected".to_string()),
+    };
+
+    let pdf = count_sheet::render(&detail);
+    std::fs::write(&export_path, pdf).map_err(|e| format!("Failed to write PDF file: {}", e))?;
+
+    app.dialog()
+        .message(format!("盤點表已儲存！\n{}", export_path))
+        .title("匯出成功")
+        .blocking_show();
+
+    Ok(export_path)
+}
+
+/// Exports the session's variances, valued at material average cost, to Excel
+#[tauri::command]
+pub async fn export_stocktake_variance(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<String, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let detail = fetch_stocktake(&mut conn, id).await?;
+    drop(conn);
+
+    let export_file = app
+        .dialog()
+        .file()
+        .add_filter("Excel", &["xlsx"])
+        .set_file_name(format!("stocktake_variance_{}.xlsx", id))
+        .blocking_save_file();
+
+    let export_path = match export_file {
+        Some(FilePath::Path(p)) => p.display().to_string(),
+        _ => return Err("No file path selected".to_string()),
+    };
+
+    let mut workbook = Workbook::new();
+    write_variance_sheet(&mut workbook, &detail)?;
+    workbook
+        .save(&export_path)
+        .map_err(|e| format!("Failed to close Excel file: {}", e))?;
+
+    app.dialog()
+        .message(format!("盤點差異報表已匯出！\n{}", export_path))
+        .title("匯出成功")
+        .blocking_show();
+
+    Ok(export_path)
+}
+
+fn write_variance_sheet(workbook: &mut Workbook, detail: &StocktakeDetail) -> Result<(), String> {
+    let worksheet = workbook
+        .add_worksheet()
+        .set_name("Stocktake")
+        .map_err(|e| format!("Failed to create Stocktake sheet: {}", e))?;
+
+    let header_format = Format::new().set_bold();
+    let headers = [
+        "類型",
+        "名稱",
+        "SKU",
+        "分類",
+        "單位",
+        "系統庫存",
+        "實盤數量",
+        "差異",
+        "單位成本",
+        "差異金額",
+        "備註",
+    ];
+
+    for (col, header) in headers.iter().enumerate() {
+        worksheet
+            .write_with_format(0, col as u16, *header, &header_format)
+            .map_err(|e| format!("Failed to write header: {}", e))?;
+    }
+
+    let mut total_value = 0.0;
+    let mut row = 1;
+    for line in &detail.lines {
+        let item_type = if line.item_type == ItemType::Material {
+            "原料"
+        } else {
+            "成品"
+        };
+        worksheet
+            .write_string(row, 0, item_type)
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+        worksheet
+            .write_string(row, 1, &line.item_name)
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+        worksheet
+            .write_string(row, 2, line.sku.as_deref().unwrap_or_default())
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+        worksheet
+            .write_string(row, 3, line.category.as_deref().unwrap_or_default())
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+        worksheet
+            .write_string(row, 4, &line.unit)
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+        worksheet
+            .write_number(row, 5, line.system_stock)
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+
+        if let Some(counted) = line.counted_quantity {
+            worksheet
+                .write_number(row, 6, counted)
+                .map_err(|e| format!("Failed to write cell: {}", e))?;
+        }
+
+        if let Some(variance) = line.variance {
+            worksheet
+                .write_number(row, 7, variance)
+                .map_err(|e| format!("Failed to write cell: {}", e))?;
+        }
+
+        if let Some(unit_cost) = line.unit_cost {
+            worksheet
+                .write_number(row, 8, unit_cost)
+                .map_err(|e| format!("Failed to write cell: {}", e))?;
+
+            if let Some(variance) = line.variance {
+                total_value += variance * unit_cost;
+                worksheet
+                    .write_number(row, 9, variance * unit_cost)
+                    .map_err(|e| format!("Failed to write cell: {}", e))?;
+            }
+        }
+
+        worksheet
+            .write_string(row, 10, line.note.as_deref().unwrap_or_default())
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+
+        row += 1;
+    }
+
+    worksheet
+        .write_with_format(row, 8, "合計", &header_format)
+        .map_err(|e| format!("Failed to write cell: {}", e))?;
+    worksheet
+        .write_number_with_format(row, 9, total_value, &header_format)
+        .map_err(|e| format!("Failed to write cell: {}", e))?;
+
+    worksheet
+        .set_column_width(1, 20)
+        .map_err(|e| format!("Failed to set column 1 width {e}"))?;
+    worksheet
+        .set_column_width(10, 30)
+        .map_err(|e| format!("Failed to set column 10 width {e}"))?;
+
+    Ok(())
+}
+
+async fn current_stock(
+    conn: &mut SqliteConnection,
+    item_type: ItemType,
+    item_id: i64,
+) -> Result<f64, String> {
+    let sql = format!(
+        "SELECT CAST(current_stock AS REAL) FROM {} WHERE id = ?",
+        item_type.table()
+    );
+    sqlx::query_scalar(&sql)
+        .bind(item_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("查無品項 id {}", item_id))
+}
+
+async fn ensure_counting(conn: &mut SqliteConnection, id: i64) -> Result<(), String> {
+    let status: StocktakeStatus =
+        sqlx::query_scalar("SELECT status FROM stocktake_sessions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("查無盤點單 id {}", id))?;
+
+    match status {
+        StocktakeStatus::Counting => Ok(()),
+        StocktakeStatus::Posted => Err(format!("盤點單 #{} 已經過帳", id)),
+        StocktakeStatus::Cancelled => Err(format!("盤點單 #{} 已經取消", id)),
+    }
+}
+
+/// Loads a stocktake session and its lines, usable from inside a transaction
+async fn fetch_stocktake(conn: &mut SqliteConnection, id: i64) -> Result<StocktakeDetail, String> {
+    let sql = format!("{} WHERE s.id = ?", STOCKTAKE_SELECT);
+    let session = sqlx::query_as::<_, StocktakeSession>(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("查無盤點單 id {}", id))?;
+
+    let lines = sqlx::query_as::<_, StocktakeLine>(
+        "SELECT sl.id, sl.session_id, sl.item_type, sl.item_id,
+                COALESCE(m.name, p.name) as item_name,
+                COALESCE(m.sku, p.sku) as sku,
+                COALESCE(m.category, p.category) as category,
+                COALESCE(m.unit, p.unit) as unit,
+                CAST(COALESCE(sl.system_stock, m.current_stock, p.current_stock) AS REAL) as system_stock,
+                sl.counted_quantity,
+                sl.counted_quantity - COALESCE(sl.system_stock, m.current_stock, p.current_stock) as variance,
+                m.avg_cost as unit_cost,
+                sl.counted_at, sl.inventory_log_id, sl.note
+         FROM stocktake_lines sl
+         LEFT JOIN materials m ON sl.item_type = 'material' AND sl.item_id = m.id
+         LEFT JOIN products p ON sl.item_type = 'product' AND sl.item_id = p.id
+         WHERE sl.session_id = ?
+         ORDER BY sl.item_type, category, item_name",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(StocktakeDetail { session, lines })
+}
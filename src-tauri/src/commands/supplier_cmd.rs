@@ -0,0 +1,238 @@
+use sqlx::SqlitePool;
+use tauri::State;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+
+use crate::models::supplier::{
+    CreateSupplierRequest, MaterialSupplier, MaterialSupplierRequest, RemoveSupplierRequest,
+    Supplier, UpdateSupplierRequest,
+};
+
+const MATERIAL_SUPPLIER_SELECT: &str = "
+    SELECT ms.material_id, m.name as material_name, m.unit as material_unit,
+           ms.supplier_id, s.name as supplier_name,
+           ms.item_code, ms.pack_size, ms.last_price, ms.lead_time_days
+    FROM material_suppliers ms
+    JOIN materials m ON ms.material_id = m.id
+    JOIN suppliers s ON ms.supplier_id = s.id";
+
+#[tauri::command]
+pub async fn list_suppliers(pool: State<'_, SqlitePool>) -> Result<Vec<Supplier>, String> {
+    let rows = sqlx::query_as::<_, Supplier>(
+        "SELECT id, name, contact, phone, email, note, created_at, deleted_at
+         FROM suppliers
+         WHERE deleted_at IS NULL
+         ORDER BY name",
+    )
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn add_supplier(
+    pool: State<'_, SqlitePool>,
+    supplier: CreateSupplierRequest,
+) -> Result<(), String> {
+    if supplier.name.trim().is_empty() {
+        return Err("供應商名稱不可為空".to_string());
+    }
+
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO suppliers
+        (name, contact, phone, email, note, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&supplier.name)
+    .bind(&supplier.contact)
+    .bind(&supplier.phone)
+    .bind(&supplier.email)
+    .bind(&supplier.note)
+    .bind(&created_at)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_supplier(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    req: UpdateSupplierRequest,
+) -> Result<(), String> {
+    if req.name.trim().is_empty() {
+        return Err("供應商名稱不可為空".to_string());
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE suppliers
+        SET name = ?, contact = ?, phone = ?, email = ?, note = ?
+        WHERE id = ? AND deleted_at IS NULL
+        "#,
+    )
+    .bind(&req.name)
+    .bind(&req.contact)
+    .bind(&req.phone)
+    .bind(&req.email)
+    .bind(&req.note)
+    .bind(id)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("查無供應商 id {}", id));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_supplier(
+    app: tauri::AppHandle,
+    pool: State<'_, SqlitePool>,
+    supplier: RemoveSupplierRequest,
+) -> Result<(), String> {
+    let answer = app
+        .dialog()
+        .message("確定要刪除這個供應商嗎？")
+        .title("確認刪除")
+        .buttons(MessageDialogButtons::OkCancel)
+        .blocking_show();
+
+    if !answer {
+        return Ok(());
+    }
+
+    let deleted_at = chrono::Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        UPDATE suppliers
+        SET deleted_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&deleted_at)
+    .bind(supplier.id)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Lists material/supplier links, filtered by material or supplier when given
+#[tauri::command]
+pub async fn list_material_suppliers(
+    pool: State<'_, SqlitePool>,
+    material_id: Option<i64>,
+    supplier_id: Option<i64>,
+) -> Result<Vec<MaterialSupplier>, String> {
+    let sql = format!(
+        "{} WHERE m.deleted_at IS NULL AND s.deleted_at IS NULL
+             AND (? IS NULL OR ms.material_id = ?)
+             AND (? IS NULL OR ms.supplier_id = ?)
+         ORDER BY m.name, s.name",
+        MATERIAL_SUPPLIER_SELECT
+    );
+    let rows = sqlx::query_as::<_, MaterialSupplier>(&sql)
+        .bind(material_id)
+        .bind(material_id)
+        .bind(supplier_id)
+        .bind(supplier_id)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Links a material to a supplier, or updates the ordering details of an existing link
+#[tauri::command]
+pub async fn set_material_supplier(
+    pool: State<'_, SqlitePool>,
+    req: MaterialSupplierRequest,
+) -> Result<(), String> {
+    if req.pack_size.is_some_and(|size| size <= 0.0) {
+        return Err("包裝數量必須大於 0".to_string());
+    }
+    if req.last_price.is_some_and(|price| price < 0.0) {
+        return Err("價格不可為負數".to_string());
+    }
+    if req.lead_time_days.is_some_and(|days| days < 0) {
+        return Err("交貨天數不可為負數".to_string());
+    }
+
+    let material_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM materials WHERE id = ? AND deleted_at IS NULL)",
+    )
+    .bind(req.material_id)
+    .fetch_one(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if !material_exists {
+        return Err(format!("查無原料 id {}", req.material_id));
+    }
+
+    let supplier_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM suppliers WHERE id = ? AND deleted_at IS NULL)",
+    )
+    .bind(req.supplier_id)
+    .fetch_one(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if !supplier_exists {
+        return Err(format!("查無供應商 id {}", req.supplier_id));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO material_suppliers
+        (material_id, supplier_id, item_code, pack_size, last_price, lead_time_days)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(material_id, supplier_id) DO UPDATE SET
+            item_code = excluded.item_code,
+            pack_size = excluded.pack_size,
+            last_price = excluded.last_price,
+            lead_time_days = excluded.lead_time_days
+        "#,
+    )
+    .bind(req.material_id)
+    .bind(req.supplier_id)
+    .bind(&req.item_code)
+    .bind(req.pack_size)
+    .bind(req.last_price)
+    .bind(req.lead_time_days)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unlink_material_supplier(
+    pool: State<'_, SqlitePool>,
+    material_id: i64,
+    supplier_id: i64,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM material_suppliers WHERE material_id = ? AND supplier_id = ?")
+        .bind(material_id)
+        .bind(supplier_id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
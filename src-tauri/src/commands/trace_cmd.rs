@@ -0,0 +1,180 @@
+use sqlx::SqlitePool;
+use tauri::State;
+
+use crate::commands::production_cmd::fetch_batch;
+use crate::models::lot::{
+    BatchMaterialUsage, BatchSale, BatchTrace, LotBatchUsage, MaterialLot, MaterialLotTrace,
+};
+
+const DEFAULT_EXPIRY_HORIZON_DAYS: i64 = 30;
+
+const LOT_SELECT: &str = "
+    SELECT ml.id, ml.material_id, m.name as material_name, m.unit as material_unit,
+           ml.lot_code, ml.received_at, ml.expires_at, ml.quantity, ml.remaining_quantity,
+           ml.inventory_log_id, ml.note, ml.created_at
+    FROM material_lots ml
+    JOIN materials m ON ml.material_id = m.id";
+
+const BATCH_SALE_SELECT: &str = "
+    SELECT ba.batch_id, pb.lot_code as batch_lot_code, il.id as inventory_log_id,
+           p.name as product_name, il.action_type, ba.quantity, il.note, il.created_at
+    FROM batch_allocations ba
+    JOIN inventory_logs il ON ba.inventory_log_id = il.id
+    JOIN production_batches pb ON ba.batch_id = pb.id
+    JOIN products p ON pb.product_id = p.id";
+
+#[tauri::command]
+pub async fn list_material_lots(
+    pool: State<'_, SqlitePool>,
+    material_id: Option<i64>,
+) -> Result<Vec<MaterialLot>, String> {
+    let sql = format!(
+        "{} WHERE ? IS NULL OR ml.material_id = ? ORDER BY ml.received_at DESC, ml.id DESC",
+        LOT_SELECT
+    );
+    let rows = sqlx::query_as::<_, MaterialLot>(&sql)
+        .bind(material_id)
+        .bind(material_id)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Lists lots with stock left that expire within `days` (default 30), including
+/// lots that have already expired
+#[tauri::command]
+pub async fn list_expiring_materials(
+    pool: State<'_, SqlitePool>,
+    days: Option<i64>,
+) -> Result<Vec<MaterialLot>, String> {
+    let days = days.unwrap_or(DEFAULT_EXPIRY_HORIZON_DAYS);
+    if days < 0 {
+        return Err("天數不可為負數".to_string());
+    }
+
+    let horizon = (chrono::Utc::now() + chrono::Duration::days(days))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let sql = format!(
+        "{} WHERE ml.remaining_quantity > 0 AND ml.expires_at IS NOT NULL
+             AND ml.expires_at <= ? AND m.deleted_at IS NULL
+         ORDER BY ml.expires_at, m.name",
+        LOT_SELECT
+    );
+    let rows = sqlx::query_as::<_, MaterialLot>(&sql)
+        .bind(&horizon)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Forward trace of a material lot to the batches that used it and where their units went
+#[tauri::command]
+pub async fn trace_material_lot(
+    pool: State<'_, SqlitePool>,
+    lot_id: i64,
+) -> Result<MaterialLotTrace, String> {
+    let sql = format!("{} WHERE ml.id = ?", LOT_SELECT);
+    let lot = sqlx::query_as::<_, MaterialLot>(&sql)
+        .bind(lot_id)
+        .fetch_optional(&*pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("查無原料批次 id {}", lot_id))?;
+
+    let batches = sqlx::query_as::<_, LotBatchUsage>(
+        "SELECT pb.id as batch_id, pb.lot_code as batch_lot_code, pb.product_id,
+                p.name as product_name, pb.produced_at, SUM(lc.quantity) as quantity_used
+         FROM lot_consumptions lc
+         JOIN inventory_logs il ON lc.inventory_log_id = il.id
+         JOIN production_batches pb ON il.batch_id = pb.id
+         JOIN products p ON pb.product_id = p.id
+         WHERE lc.material_lot_id = ?
+         GROUP BY pb.id
+         ORDER BY pb.produced_at",
+    )
+    .bind(lot_id)
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let sql = format!(
+        "{} WHERE ba.batch_id IN (
+             SELECT il.batch_id FROM lot_consumptions lc
+             JOIN inventory_logs il ON lc.inventory_log_id = il.id
+             WHERE lc.material_lot_id = ?
+         )
+         ORDER BY il.created_at",
+        BATCH_SALE_SELECT
+    );
+    let sales = sqlx::query_as::<_, BatchSale>(&sql)
+        .bind(lot_id)
+        .fetch_all(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(MaterialLotTrace {
+        lot,
+        batches,
+        sales,
+    })
+}
+
+/// Backward trace of a production batch to the material lots it consumed
+#[tauri::command]
+pub async fn trace_production_batch(
+    pool: State<'_, SqlitePool>,
+    batch_id: i64,
+) -> Result<BatchTrace, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    let batch = fetch_batch(&mut conn, batch_id).await?;
+
+    // Lot-tracked consumption, plus whatever part of each movement had no lot
+    let materials = sqlx::query_as::<_, BatchMaterialUsage>(
+        "SELECT il.item_id as material_id, m.name as material_name, m.unit as material_unit,
+                ml.id as material_lot_id, ml.lot_code, ml.received_at, lc.quantity
+         FROM inventory_logs il
+         JOIN materials m ON il.item_id = m.id
+         JOIN lot_consumptions lc ON lc.inventory_log_id = il.id
+         JOIN material_lots ml ON lc.material_lot_id = ml.id
+         WHERE il.batch_id = ? AND il.item_type = 'material'
+         UNION ALL
+         SELECT il.item_id, m.name, m.unit, NULL, NULL, NULL,
+                (il.old_stock - il.new_stock)
+                - COALESCE((SELECT SUM(quantity) FROM lot_consumptions WHERE inventory_log_id = il.id), 0)
+                as quantity
+         FROM inventory_logs il
+         JOIN materials m ON il.item_id = m.id
+         WHERE il.batch_id = ? AND il.item_type = 'material'
+           AND (il.old_stock - il.new_stock)
+               - COALESCE((SELECT SUM(quantity) FROM lot_consumptions WHERE inventory_log_id = il.id), 0) > 1e-9
+         ORDER BY material_name",
+    )
+    .bind(batch_id)
+    .bind(batch_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let sql = format!(
+        "{} WHERE ba.batch_id = ? ORDER BY il.created_at",
+        BATCH_SALE_SELECT
+    );
+    let sales = sqlx::query_as::<_, BatchSale>(&sql)
+        .bind(batch_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(BatchTrace {
+        batch,
+        materials,
+        sales,
+    })
+}
@@ -1,7 +1,9 @@
 use sqlx::{Row, SqlitePool, Transaction};
 
+use crate::error::AppError;
+
 /// Migration system that tracks applied migrations and runs them incrementally
-pub async fn migrate(pool: &SqlitePool) -> anyhow::Result<(), String> {
+pub async fn migrate(pool: &SqlitePool) -> Result<(), AppError> {
     // Create migrations tracking table if it doesn't exist
     sqlx::query(
         r#"
@@ -13,8 +15,7 @@ pub async fn migrate(pool: &SqlitePool) -> anyhow::Result<(), String> {
         "#,
     )
     .execute(pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
 
     // Run all migrations in order
     run_migration(
@@ -24,11 +25,165 @@ pub async fn migrate(pool: &SqlitePool) -> anyhow::Result<(), String> {
         include_str!("migrations/001_initial_schema.sql"),
     )
     .await?;
+    run_migration(
+        pool,
+        3,
+        "003_recipes",
+        include_str!("migrations/003_recipes.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        4,
+        "004_production_batches",
+        include_str!("migrations/004_production_batches.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        5,
+        "005_lye_calculation",
+        include_str!("migrations/005_lye_calculation.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        6,
+        "006_fatty_acids",
+        include_str!("migrations/006_fatty_acids.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        7,
+        "007_curing",
+        include_str!("migrations/007_curing.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        8,
+        "008_lot_tracking",
+        include_str!("migrations/008_lot_tracking.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        9,
+        "009_lot_expiry",
+        include_str!("migrations/009_lot_expiry.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        10,
+        "010_suppliers",
+        include_str!("migrations/010_suppliers.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        11,
+        "011_purchase_orders",
+        include_str!("migrations/011_purchase_orders.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        12,
+        "012_material_cost",
+        include_str!("migrations/012_material_cost.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        13,
+        "013_customers",
+        include_str!("migrations/013_customers.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        14,
+        "014_sales_orders",
+        include_str!("migrations/014_sales_orders.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        15,
+        "015_sales_returns",
+        include_str!("migrations/015_sales_returns.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        16,
+        "016_product_prices",
+        include_str!("migrations/016_product_prices.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        17,
+        "017_invoice_numbers",
+        include_str!("migrations/017_invoice_numbers.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        18,
+        "018_inci_names",
+        include_str!("migrations/018_inci_names.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        19,
+        "019_sku_codes",
+        include_str!("migrations/019_sku_codes.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        20,
+        "020_stocktakes",
+        include_str!("migrations/020_stocktakes.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        21,
+        "021_void_movements",
+        include_str!("migrations/021_void_movements.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        22,
+        "022_adjustment_deltas",
+        include_str!("migrations/022_adjustment_deltas.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        23,
+        "023_movement_type_checks",
+        include_str!("migrations/023_movement_type_checks.sql"),
+    )
+    .await?;
+    run_migration(
+        pool,
+        24,
+        "024_material_density",
+        include_str!("migrations/024_material_density.sql"),
+    )
+    .await?;
     // Add future migrations here:
     // run_migration(pool, 2, "002_add_supplier_column", include_str!("migrations/002_add_supplier_column.sql")).await?;
 
     // Seed test data only on fresh installations
-    seed_test_data(pool).await.map_err(|e| e.to_string())?;
+    seed_test_data(pool).await?;
 
     Ok(())
 }
@@ -39,18 +194,16 @@ async fn run_migration(
     version: i64,
     name: &str,
     sql: &str,
-) -> anyhow::Result<(), String> {
+) -> Result<(), AppError> {
     // Check if this migration has already been applied
     let applied: bool =
         sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM _migrations WHERE version = ?)")
             .bind(version)
             .fetch_one(pool)
-            .await
-            .map_err(|e| e.to_string())?;
+            .await?;
 
     if !applied {
-        let mut tx: Transaction<'_, sqlx::Sqlite> =
-            pool.begin().await.map_err(|e| e.to_string())?;
+        let mut tx: Transaction<'_, sqlx::Sqlite> = pool.begin().await?;
 
         println!("Running migration {}: {}", version, name);
 
@@ -58,7 +211,7 @@ async fn run_migration(
         sqlx::query(sql)
             .execute(&mut *tx)
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| AppError::Database(format!("Migration {} failed: {}", name, e)))?;
 
         // Record that this migration has been applied
         sqlx::query(
@@ -67,10 +220,9 @@ async fn run_migration(
         .bind(version)
         .bind(name)
         .execute(&mut *tx)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
-        tx.commit().await.map_err(|e| e.to_string())?;
+        tx.commit().await?;
         println!("Migration {} completed successfully", version);
     } else {
         println!("Migration {} already applied, skipping", version);
@@ -97,11 +249,11 @@ async fn seed_test_data(pool: &SqlitePool) -> Result<(), sqlx::Error> {
 
         sqlx::query(
             r#"
-            INSERT INTO materials (name, category, unit, current_stock, low_stock_alert, created_at) VALUES
-            ('橄欖油', '油品', 'ml', 5000, 1000, datetime('now')),
-            ('椰子油', '油品', 'ml', 3000, 500, datetime('now')),
-            ('氫氧化鈉', '添加物', 'g', 2000, 200, datetime('now')),
-            ('薰衣草精油', '精油 / 香精', 'ml', 200, 50, datetime('now'))
+            INSERT INTO materials (name, category, sku, unit, current_stock, low_stock_alert, sap_naoh, sap_koh, density, created_at) VALUES
+            ('橄欖油', '油品', 'M00001', 'ml', 5000, 1000, 0.1345, 0.1900, 0.91, datetime('now')),
+            ('椰子油', '油品', 'M00002', 'ml', 3000, 500, 0.1830, 0.2570, 0.92, datetime('now')),
+            ('氫氧化鈉', '添加物', 'M00003', 'g', 2000, 200, NULL, NULL, NULL, datetime('now')),
+            ('薰衣草精油', '精油 / 香精', 'M00004', 'ml', 200, 50, NULL, NULL, NULL, datetime('now'))
             "#
         )
         .execute(pool)
@@ -109,11 +261,52 @@ async fn seed_test_data(pool: &SqlitePool) -> Result<(), sqlx::Error> {
 
         sqlx::query(
             r#"
-            INSERT INTO products (name, category, unit, current_stock, note, created_at) VALUES
-            ('薰衣草手工皂', '沐浴', '個', 50, '100g 手工皂', datetime('now')),
-            ('橄欖油手工皂', '沐浴', '個', 30, '100g 手工皂', datetime('now')),
-            ('蜜糖燕麥皂', '沐浴', '個', 45, '100g 手工皂', datetime('now')),
-            ('活性炭皂', '沐浴', '個', 25, '100g 手工皂', datetime('now'))
+            INSERT INTO inventory_logs (item_id, item_type, action_type, change_amount, old_stock, new_stock, note, created_at)
+            SELECT id, 'material', 'in', current_stock, 0, current_stock, 'Initial stock', datetime('now') FROM materials
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO material_lots (material_id, received_at, quantity, remaining_quantity, inventory_log_id, note, created_at)
+            SELECT m.id, m.created_at, m.current_stock, m.current_stock, il.id, '期初庫存', datetime('now')
+            FROM materials m
+            JOIN inventory_logs il ON il.item_type = 'material' AND il.item_id = m.id
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO material_fatty_acids
+            (material_id, lauric, myristic, palmitic, stearic, ricinoleic, oleic, linoleic, linolenic)
+            SELECT id, 0, 0, 14, 3, 0, 69, 12, 1 FROM materials WHERE name = '橄欖油'
+            UNION ALL
+            SELECT id, 48, 19, 9, 3, 0, 8, 2, 0 FROM materials WHERE name = '椰子油'
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO products (name, category, sku, unit, current_stock, note, created_at) VALUES
+            ('薰衣草手工皂', '沐浴', 'P00001', '個', 50, '100g 手工皂', datetime('now')),
+            ('橄欖油手工皂', '沐浴', 'P00002', '個', 30, '100g 手工皂', datetime('now')),
+            ('蜜糖燕麥皂', '沐浴', 'P00003', '個', 45, '100g 手工皂', datetime('now')),
+            ('活性炭皂', '沐浴', 'P00004', '個', 25, '100g 手工皂', datetime('now'))
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO inventory_logs (item_id, item_type, action_type, change_amount, old_stock, new_stock, note, created_at)
+            SELECT id, 'product', 'in', current_stock, 0, current_stock, 'Initial stock', datetime('now') FROM products
             "#,
         )
         .execute(pool)
@@ -0,0 +1,22 @@
+-- Migration 003: Recipes (bill of materials) linking products to materials
+CREATE TABLE IF NOT EXISTS recipes (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id      INTEGER NOT NULL,
+    name            TEXT NOT NULL,
+    yield_quantity  INTEGER NOT NULL DEFAULT 1,   -- finished units per batch
+    note            TEXT,
+    created_at      TEXT NOT NULL,
+    FOREIGN KEY(product_id) REFERENCES products(id)
+);
+
+CREATE TABLE IF NOT EXISTS recipe_lines (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    recipe_id    INTEGER NOT NULL,
+    material_id  INTEGER NOT NULL,
+    quantity     REAL NOT NULL,                   -- in the material's unit, per batch
+    FOREIGN KEY(recipe_id) REFERENCES recipes(id),
+    FOREIGN KEY(material_id) REFERENCES materials(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_recipes_product ON recipes(product_id);
+CREATE INDEX IF NOT EXISTS idx_recipe_lines_recipe ON recipe_lines(recipe_id);
@@ -0,0 +1,9 @@
+-- Migration 004: Link production batches to recipes and inventory logs
+ALTER TABLE production_batches ADD COLUMN recipe_id INTEGER REFERENCES recipes(id);
+ALTER TABLE production_batches ADD COLUMN batch_count INTEGER NOT NULL DEFAULT 1;
+
+-- Every material consumed and product produced by a batch points back to it
+ALTER TABLE inventory_logs ADD COLUMN batch_id INTEGER REFERENCES production_batches(id);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_batch ON inventory_logs(batch_id);
+CREATE INDEX IF NOT EXISTS idx_production_batches_product ON production_batches(product_id);
@@ -0,0 +1,11 @@
+-- Migration 005: Saponification values on materials and lye settings on recipes
+-- SAP values are grams of lye needed to saponify one gram of oil
+ALTER TABLE materials ADD COLUMN sap_naoh REAL;
+ALTER TABLE materials ADD COLUMN sap_koh REAL;
+
+-- When lye_material_id is set, production deducts the calculated lye amount
+ALTER TABLE recipes ADD COLUMN lye_material_id INTEGER REFERENCES materials(id);
+ALTER TABLE recipes ADD COLUMN lye_type TEXT;            -- 'naoh' or 'koh'
+ALTER TABLE recipes ADD COLUMN superfat REAL;            -- percent
+ALTER TABLE recipes ADD COLUMN water_ratio REAL;         -- water : lye, e.g. 2.5
+ALTER TABLE recipes ADD COLUMN lye_concentration REAL;   -- percent, alternative to water_ratio
@@ -0,0 +1,13 @@
+-- Migration 006: Fatty-acid composition of oil materials (percent of total fatty acids)
+CREATE TABLE IF NOT EXISTS material_fatty_acids (
+    material_id  INTEGER PRIMARY KEY,
+    lauric       REAL NOT NULL DEFAULT 0,
+    myristic     REAL NOT NULL DEFAULT 0,
+    palmitic     REAL NOT NULL DEFAULT 0,
+    stearic      REAL NOT NULL DEFAULT 0,
+    ricinoleic   REAL NOT NULL DEFAULT 0,
+    oleic        REAL NOT NULL DEFAULT 0,
+    linoleic     REAL NOT NULL DEFAULT 0,
+    linolenic    REAL NOT NULL DEFAULT 0,
+    FOREIGN KEY(material_id) REFERENCES materials(id)
+);
@@ -0,0 +1,12 @@
+-- Migration 007: Curing tracker for production batches
+-- Bars of a batch count as curing stock until the batch is released
+ALTER TABLE production_batches ADD COLUMN cure_started_at TEXT;
+ALTER TABLE production_batches ADD COLUMN cure_ready_at TEXT;
+ALTER TABLE production_batches ADD COLUMN released_at TEXT;
+
+-- Batches recorded before curing was tracked are treated as already sellable
+UPDATE production_batches
+SET cure_started_at = produced_at, cure_ready_at = produced_at, released_at = produced_at
+WHERE released_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_production_batches_released ON production_batches(released_at);
@@ -0,0 +1,58 @@
+-- Migration 008: Material lots, batch lot codes and traceability links
+CREATE TABLE IF NOT EXISTS material_lots (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+    material_id         INTEGER NOT NULL,
+    lot_code            TEXT,                  -- supplier lot code
+    received_at         TEXT NOT NULL,
+    quantity            REAL NOT NULL,         -- quantity received
+    remaining_quantity  REAL NOT NULL,         -- not yet consumed
+    inventory_log_id    INTEGER,               -- receiving inventory_logs row
+    note                TEXT,
+    created_at          TEXT NOT NULL,
+    FOREIGN KEY(material_id) REFERENCES materials(id)
+);
+
+-- Which lots each material movement drew from
+CREATE TABLE IF NOT EXISTS lot_consumptions (
+    id                INTEGER PRIMARY KEY AUTOINCREMENT,
+    inventory_log_id  INTEGER NOT NULL,        -- consuming inventory_logs row
+    material_lot_id   INTEGER NOT NULL,
+    quantity          REAL NOT NULL,
+    FOREIGN KEY(material_lot_id) REFERENCES material_lots(id)
+);
+
+ALTER TABLE production_batches ADD COLUMN lot_code TEXT;
+ALTER TABLE production_batches ADD COLUMN remaining_quantity INTEGER NOT NULL DEFAULT 0;
+
+-- Which production batches each product movement drew from
+CREATE TABLE IF NOT EXISTS batch_allocations (
+    id                INTEGER PRIMARY KEY AUTOINCREMENT,
+    inventory_log_id  INTEGER NOT NULL,        -- consuming inventory_logs row
+    batch_id          INTEGER NOT NULL,
+    quantity          INTEGER NOT NULL,
+    FOREIGN KEY(batch_id) REFERENCES production_batches(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_material_lots_material ON material_lots(material_id, remaining_quantity);
+CREATE INDEX IF NOT EXISTS idx_lot_consumptions_log ON lot_consumptions(inventory_log_id);
+CREATE INDEX IF NOT EXISTS idx_lot_consumptions_lot ON lot_consumptions(material_lot_id);
+CREATE INDEX IF NOT EXISTS idx_batch_allocations_log ON batch_allocations(inventory_log_id);
+CREATE INDEX IF NOT EXISTS idx_batch_allocations_batch ON batch_allocations(batch_id);
+
+-- Existing stock becomes one opening lot per material
+INSERT INTO material_lots (material_id, lot_code, received_at, quantity, remaining_quantity, note, created_at)
+SELECT id, NULL, created_at, current_stock, current_stock, '期初庫存', datetime('now')
+FROM materials
+WHERE current_stock > 0;
+
+-- Bars already sold came out of the oldest batches first, so the stock on hand
+-- is credited to the newest batches of each product
+UPDATE production_batches
+SET lot_code = 'B' || replace(substr(produced_at, 1, 10), '-', '') || '-' || printf('%04d', id),
+    remaining_quantity = MAX(0, MIN(quantity,
+        (SELECT current_stock FROM products WHERE id = production_batches.product_id)
+        - (SELECT COALESCE(SUM(newer.quantity), 0) FROM production_batches newer
+           WHERE newer.product_id = production_batches.product_id
+             AND (newer.produced_at > production_batches.produced_at
+                  OR (newer.produced_at = production_batches.produced_at
+                      AND newer.id > production_batches.id)))));
@@ -0,0 +1,4 @@
+-- Migration 009: Expiry dates on material lots (YYYY-MM-DD)
+ALTER TABLE material_lots ADD COLUMN expires_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_material_lots_expires ON material_lots(expires_at);
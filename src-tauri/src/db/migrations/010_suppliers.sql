@@ -0,0 +1,20 @@
+-- Migration 010: Supplier soft delete and material <-> supplier links
+ALTER TABLE suppliers ADD COLUMN created_at TEXT NOT NULL DEFAULT '';
+ALTER TABLE suppliers ADD COLUMN deleted_at TEXT;
+
+UPDATE suppliers SET created_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE created_at = '';
+
+-- Where each material can be reordered from
+CREATE TABLE IF NOT EXISTS material_suppliers (
+    material_id     INTEGER NOT NULL,
+    supplier_id     INTEGER NOT NULL,
+    item_code       TEXT,                  -- supplier's own item code
+    pack_size       REAL,                  -- in the material's unit
+    last_price      REAL,                  -- price per pack
+    lead_time_days  INTEGER,
+    PRIMARY KEY (material_id, supplier_id),
+    FOREIGN KEY(material_id) REFERENCES materials(id),
+    FOREIGN KEY(supplier_id) REFERENCES suppliers(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_material_suppliers_supplier ON material_suppliers(supplier_id);
@@ -0,0 +1,31 @@
+-- Migration 011: Purchase orders and unit cost on inventory logs
+CREATE TABLE IF NOT EXISTS purchase_orders (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    supplier_id   INTEGER NOT NULL,
+    status        TEXT NOT NULL DEFAULT 'draft',  -- draft / ordered / partially_received / received / cancelled
+    ordered_at    TEXT,
+    expected_at   TEXT,
+    received_at   TEXT,                           -- set once every line is fully received
+    cancelled_at  TEXT,
+    note          TEXT,
+    created_at    TEXT NOT NULL,
+    FOREIGN KEY(supplier_id) REFERENCES suppliers(id)
+);
+
+CREATE TABLE IF NOT EXISTS purchase_order_lines (
+    id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+    purchase_order_id  INTEGER NOT NULL,
+    material_id        INTEGER NOT NULL,
+    quantity           REAL NOT NULL,             -- ordered, in the material's unit
+    received_quantity  REAL NOT NULL DEFAULT 0,
+    unit_cost          REAL,                      -- per material unit
+    FOREIGN KEY(purchase_order_id) REFERENCES purchase_orders(id),
+    FOREIGN KEY(material_id) REFERENCES materials(id)
+);
+
+ALTER TABLE inventory_logs ADD COLUMN unit_cost REAL;
+ALTER TABLE inventory_logs ADD COLUMN purchase_order_id INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_purchase_orders_status ON purchase_orders(status);
+CREATE INDEX IF NOT EXISTS idx_purchase_order_lines_order ON purchase_order_lines(purchase_order_id);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_purchase_order ON inventory_logs(purchase_order_id);
@@ -0,0 +1,11 @@
+-- Migration 012: Moving weighted-average cost on materials
+ALTER TABLE materials ADD COLUMN avg_cost REAL;  -- per material unit
+
+-- Start from the average of every costed receipt so far
+UPDATE materials
+SET avg_cost = (
+    SELECT SUM(il.change_amount * il.unit_cost) / SUM(il.change_amount)
+    FROM inventory_logs il
+    WHERE il.item_type = 'material' AND il.item_id = materials.id
+      AND il.action_type = 'in' AND il.unit_cost IS NOT NULL AND il.change_amount > 0
+);
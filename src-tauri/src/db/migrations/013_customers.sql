@@ -0,0 +1,8 @@
+-- Migration 013: Customer soft delete
+ALTER TABLE customers ADD COLUMN created_at TEXT NOT NULL DEFAULT '';
+ALTER TABLE customers ADD COLUMN deleted_at TEXT;
+
+UPDATE customers SET created_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE created_at = '';
+
+CREATE INDEX IF NOT EXISTS idx_sales_orders_customer ON sales_orders(customer_id);
+CREATE INDEX IF NOT EXISTS idx_sales_items_order ON sales_items(order_id);
@@ -0,0 +1,9 @@
+-- Migration 014: Link sales orders to the inventory ledger
+ALTER TABLE sales_orders ADD COLUMN created_at TEXT NOT NULL DEFAULT '';
+ALTER TABLE sales_items ADD COLUMN inventory_log_id INTEGER;  -- inventory_logs.id of the stock deduction
+ALTER TABLE inventory_logs ADD COLUMN sales_order_id INTEGER;
+
+UPDATE sales_orders SET created_at = order_date WHERE created_at = '';
+
+CREATE INDEX IF NOT EXISTS idx_sales_orders_date ON sales_orders(order_date DESC);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_sales_order ON inventory_logs(sales_order_id);
@@ -0,0 +1,29 @@
+-- Migration 015: Sales order cancellation, returns and quarantined stock
+ALTER TABLE sales_orders ADD COLUMN status TEXT NOT NULL DEFAULT 'completed';  -- completed / partially_returned / returned / cancelled
+ALTER TABLE sales_orders ADD COLUMN refund_amount REAL NOT NULL DEFAULT 0;
+ALTER TABLE sales_orders ADD COLUMN cancelled_at TEXT;
+
+ALTER TABLE sales_items ADD COLUMN returned_quantity INTEGER NOT NULL DEFAULT 0;
+
+-- Movement a compensating entry reverses
+ALTER TABLE inventory_logs ADD COLUMN reversal_of INTEGER;  -- inventory_logs.id
+
+-- Damaged bars held back from sellable stock
+ALTER TABLE products ADD COLUMN quarantine_stock INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS sales_returns (
+    id                INTEGER PRIMARY KEY AUTOINCREMENT,
+    order_id          INTEGER NOT NULL,
+    sales_item_id     INTEGER NOT NULL,
+    quantity          INTEGER NOT NULL,
+    condition         TEXT NOT NULL,            -- sellable / damaged
+    refund_amount     REAL NOT NULL DEFAULT 0,
+    inventory_log_id  INTEGER,                  -- inventory_logs.id of the return entry
+    note              TEXT,
+    created_at        TEXT NOT NULL,
+    FOREIGN KEY(order_id) REFERENCES sales_orders(id),
+    FOREIGN KEY(sales_item_id) REFERENCES sales_items(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sales_returns_order ON sales_returns(order_id);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_reversal ON inventory_logs(reversal_of);
@@ -0,0 +1,17 @@
+-- Migration 016: Retail / wholesale prices with history and customer price tiers
+ALTER TABLE products ADD COLUMN retail_price REAL;
+ALTER TABLE products ADD COLUMN wholesale_price REAL;
+
+-- Every price change, dated, newest row is the current price
+CREATE TABLE IF NOT EXISTS product_prices (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id       INTEGER NOT NULL,
+    retail_price     REAL,
+    wholesale_price  REAL,
+    effective_at     TEXT NOT NULL,
+    FOREIGN KEY(product_id) REFERENCES products(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_prices_product ON product_prices(product_id, effective_at DESC);
+
+ALTER TABLE customers ADD COLUMN price_tier TEXT NOT NULL DEFAULT 'retail';  -- retail / wholesale
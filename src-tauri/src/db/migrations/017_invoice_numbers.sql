@@ -0,0 +1,5 @@
+-- Migration 017: Invoice numbers for sales orders
+-- Assigned from the invoice sequence in app_settings the first time a receipt is printed
+ALTER TABLE sales_orders ADD COLUMN invoice_number TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sales_orders_invoice_number ON sales_orders(invoice_number);
@@ -0,0 +1,4 @@
+-- Migration 018: INCI names for cosmetic labels
+ALTER TABLE materials ADD COLUMN inci_name TEXT;
+-- Name of the sodium salt an oil becomes once saponified, e.g. Sodium Olivate
+ALTER TABLE materials ADD COLUMN saponified_inci_name TEXT;
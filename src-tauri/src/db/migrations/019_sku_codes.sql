@@ -0,0 +1,17 @@
+-- Migration 019: SKU codes for products and materials, looked up by barcode scanners
+ALTER TABLE materials ADD COLUMN sku TEXT;
+
+-- Blank codes become NULL so they are filled in below
+UPDATE products SET sku = NULL WHERE TRIM(sku) = '';
+
+-- Codes entered before the column was surfaced may repeat; keep the oldest
+UPDATE products SET sku = sku || '-' || id
+WHERE sku IS NOT NULL
+  AND id NOT IN (SELECT MIN(id) FROM products WHERE sku IS NOT NULL GROUP BY sku);
+
+UPDATE products SET sku = 'P' || printf('%05d', id) WHERE sku IS NULL;
+UPDATE materials SET sku = 'M' || printf('%05d', id) WHERE sku IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_products_sku ON products(sku);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_materials_sku ON materials(sku);
+CREATE INDEX IF NOT EXISTS idx_material_suppliers_item_code ON material_suppliers(item_code);
@@ -0,0 +1,31 @@
+-- Migration 020: Stocktake sessions
+-- A session covers every material and/or product, optionally limited to one category
+CREATE TABLE IF NOT EXISTS stocktake_sessions (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    name          TEXT NOT NULL,
+    item_type     TEXT,                          -- material / product, NULL for both
+    category      TEXT,                          -- NULL for every category
+    status        TEXT NOT NULL DEFAULT 'counting',  -- counting / posted / cancelled
+    note          TEXT,
+    started_at    TEXT NOT NULL,
+    posted_at     TEXT,
+    cancelled_at  TEXT
+);
+
+CREATE TABLE IF NOT EXISTS stocktake_lines (
+    id                INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id        INTEGER NOT NULL,
+    item_type         TEXT NOT NULL,
+    item_id           INTEGER NOT NULL,
+    counted_quantity  REAL,                      -- NULL until counted
+    counted_at        TEXT,
+    system_stock      REAL,                      -- stock on record when the line was counted
+    inventory_log_id  INTEGER,                   -- inventory_logs.id of the adjustment
+    note              TEXT,
+    FOREIGN KEY(session_id) REFERENCES stocktake_sessions(id),
+    UNIQUE(session_id, item_type, item_id)
+);
+
+ALTER TABLE inventory_logs ADD COLUMN stocktake_session_id INTEGER REFERENCES stocktake_sessions(id);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_stocktake ON inventory_logs(stocktake_session_id);
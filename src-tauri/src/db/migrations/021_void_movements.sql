@@ -0,0 +1,4 @@
+-- Migration 021: Voided movements
+-- A voided entry stays in the history; a reversing entry (reversal_of) undoes its stock change
+ALTER TABLE inventory_logs ADD COLUMN voided_at TEXT;
+ALTER TABLE inventory_logs ADD COLUMN voided_by INTEGER;  -- inventory_logs.id of the reversing entry
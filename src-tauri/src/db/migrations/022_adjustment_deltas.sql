@@ -0,0 +1,10 @@
+-- Migration 022: Adjustments store a signed delta
+-- change_amount on adj rows held the requested stock level; it now holds the signed
+-- new_stock - old_stock, and the request moves to target_stock. Other rows keep a
+-- positive quantity whose direction follows from action_type.
+ALTER TABLE inventory_logs ADD COLUMN target_stock REAL;  -- stock level an adj entry was asked to set
+
+UPDATE inventory_logs
+SET target_stock = change_amount,
+    change_amount = new_stock - old_stock
+WHERE action_type = 'adj';
@@ -0,0 +1,71 @@
+-- Migration 023: Restrict inventory_logs item and action types
+-- SQLite cannot add a CHECK constraint to an existing table, so the table is rebuilt.
+-- No other table declares a foreign key to inventory_logs, so ids are copied as-is.
+
+-- Rows written before the types were enforced are normalised first. Anything that
+-- is not a material was treated as a product, so it stays one.
+UPDATE inventory_logs SET item_type = lower(trim(item_type));
+UPDATE inventory_logs SET item_type = 'product' WHERE item_type <> 'material';
+
+-- The original schema named its actions purchase / production / sale / adjust
+UPDATE inventory_logs SET action_type = lower(trim(action_type));
+UPDATE inventory_logs SET action_type = 'in' WHERE action_type = 'purchase';
+UPDATE inventory_logs
+SET action_type = 'adj',
+    target_stock = new_stock,
+    change_amount = new_stock - old_stock
+WHERE action_type IN ('adjust', 'adjustment');
+-- Materials consumed by production are outs; only the bars a batch adds count as production
+UPDATE inventory_logs SET action_type = 'out'
+WHERE action_type = 'production' AND new_stock < old_stock;
+UPDATE inventory_logs SET action_type = 'production'
+WHERE action_type = 'in' AND item_type = 'product' AND batch_id IS NOT NULL;
+-- Any other unknown action is read from the direction the stock moved
+UPDATE inventory_logs
+SET action_type = CASE WHEN new_stock < old_stock THEN 'out' ELSE 'in' END,
+    change_amount = abs(new_stock - old_stock)
+WHERE action_type NOT IN ('in', 'out', 'adj', 'sale', 'production', 'waste', 'return', 'quarantine');
+
+CREATE TABLE inventory_logs_new (
+    id                    INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_type             TEXT NOT NULL CHECK (item_type IN ('material', 'product')),
+    item_id               INTEGER NOT NULL,      -- material_id or product_id
+    change_amount         REAL NOT NULL,         -- quantity moved; signed for adj
+    old_stock             REAL NOT NULL,         -- stock before change
+    new_stock             REAL NOT NULL,         -- stock after change
+    action_type           TEXT NOT NULL
+        CHECK (action_type IN ('in', 'out', 'adj', 'sale', 'production', 'waste', 'return', 'quarantine')),
+    note                  TEXT,
+    created_at            TEXT NOT NULL,
+    batch_id              INTEGER REFERENCES production_batches(id),
+    unit_cost             REAL,
+    purchase_order_id     INTEGER,
+    sales_order_id        INTEGER,
+    reversal_of           INTEGER,               -- inventory_logs.id
+    stocktake_session_id  INTEGER REFERENCES stocktake_sessions(id),
+    voided_at             TEXT,
+    voided_by             INTEGER,               -- inventory_logs.id of the reversing entry
+    target_stock          REAL                   -- stock level an adj entry was asked to set
+);
+
+INSERT INTO inventory_logs_new (
+    id, item_type, item_id, change_amount, old_stock, new_stock, action_type, note, created_at,
+    batch_id, unit_cost, purchase_order_id, sales_order_id, reversal_of, stocktake_session_id,
+    voided_at, voided_by, target_stock
+)
+SELECT
+    id, item_type, item_id, change_amount, old_stock, new_stock, action_type, note, created_at,
+    batch_id, unit_cost, purchase_order_id, sales_order_id, reversal_of, stocktake_session_id,
+    voided_at, voided_by, target_stock
+FROM inventory_logs;
+
+DROP TABLE inventory_logs;
+ALTER TABLE inventory_logs_new RENAME TO inventory_logs;
+
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_item ON inventory_logs(item_type, item_id);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_created ON inventory_logs(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_batch ON inventory_logs(batch_id);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_purchase_order ON inventory_logs(purchase_order_id);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_sales_order ON inventory_logs(sales_order_id);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_reversal ON inventory_logs(reversal_of);
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_stocktake ON inventory_logs(stocktake_session_id);
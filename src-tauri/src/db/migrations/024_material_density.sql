@@ -0,0 +1,3 @@
+-- Migration 024: Material density
+-- Oils stocked by volume (ml / L) need a density to be weighed for lye calculations
+ALTER TABLE materials ADD COLUMN density REAL;  -- g per ml
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::models::movement::StockShortage;
+
+/// Error returned by Tauri commands. Reaches the frontend as
+/// `{ code, message, details }` so it can tell failures apart.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound(String),
+    /// Input rejected before touching the database; `fields` names the offending inputs
+    Validation {
+        message: String,
+        fields: Vec<FieldError>,
+    },
+    /// The request clashes with data already stored, e.g. a duplicate or a constraint
+    Conflict(String),
+    InsufficientStock(StockShortage),
+    Database(String),
+    Io(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation {
+            message: message.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// A validation error caused by a single input field
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        AppError::Validation {
+            fields: vec![FieldError {
+                field: field.to_string(),
+                message: message.clone(),
+            }],
+            message,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::Validation { .. } => "validation",
+            AppError::Conflict(_) => "conflict",
+            AppError::InsufficientStock(_) => "insufficient_stock",
+            AppError::Database(_) => "database",
+            AppError::Io(_) => "io",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound(message)
+            | AppError::Validation { message, .. }
+            | AppError::Conflict(message) => write!(f, "{}", message),
+            AppError::InsufficientStock(s) => write!(
+                f,
+                "「{}」庫存不足：現有 {}{}，需要 {}{}，不足 {}{}",
+                s.item_name, s.current_stock, s.unit, s.requested, s.unit, s.shortfall, s.unit
+            ),
+            AppError::Database(message) => write!(f, "資料庫錯誤：{}", message),
+            AppError::Io(message) => write!(f, "檔案存取失敗：{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        match self {
+            AppError::Validation { fields, .. } if !fields.is_empty() => {
+                state.serialize_field("details", fields)?
+            }
+            AppError::InsufficientStock(shortage) => state.serialize_field("details", shortage)?,
+            _ => state.serialize_field("details", &())?,
+        }
+        state.end()
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::RowNotFound => AppError::NotFound("查無資料".to_string()),
+            sqlx::Error::Database(db)
+                if db.is_unique_violation()
+                    || db.is_foreign_key_violation()
+                    || db.is_check_violation() =>
+            {
+                AppError::Conflict(db.message().to_string())
+            }
+            _ => AppError::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+/// Commands that still return `String` errors pass structured errors on as
+/// their JSON form, which the frontend parses the same way
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        serde_json::to_string(&e).unwrap_or_else(|_| e.to_string())
+    }
+}
@@ -1,6 +1,9 @@
+mod barcode;
 mod commands;
 mod db;
+mod error;
 mod models;
+mod pdf;
 
 use tauri::Manager;
 
@@ -26,23 +29,86 @@ fn main() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
+            commands::code_cmd::find_item_by_code,
+            commands::code_cmd::generate_barcode,
+            commands::code_cmd::export_barcode,
+            commands::cost_cmd::inventory_valuation,
+            commands::cost_cmd::get_product_cost,
+            commands::customer_cmd::list_customers,
+            commands::customer_cmd::add_customer,
+            commands::customer_cmd::update_customer,
+            commands::customer_cmd::remove_customer,
+            commands::customer_cmd::get_customer_history,
             commands::db_cmd::export_database,
             commands::db_cmd::export_database_excel,
             commands::db_cmd::import_database,
+            commands::formula_cmd::calculate_lye,
+            commands::formula_cmd::calculate_soap_quality,
+            commands::label_cmd::get_batch_label,
+            commands::label_cmd::generate_label,
+            commands::label_cmd::get_label_sheets,
+            commands::label_cmd::save_label_png,
+            commands::ledger_cmd::verify_stock_ledger,
             commands::material_cmd::list_materials,
             commands::material_cmd::add_material,
             commands::material_cmd::update_material,
             commands::material_cmd::remove_material,
+            commands::material_cmd::get_material_fatty_acids,
+            commands::material_cmd::set_material_fatty_acids,
             commands::product_cmd::list_products,
             commands::product_cmd::add_product,
             commands::product_cmd::update_product,
             commands::product_cmd::get_product,
             commands::product_cmd::remove_product,
+            commands::product_cmd::list_product_prices,
             commands::movement_cmd::list_movements,
             commands::movement_cmd::add_inventory,
             commands::movement_cmd::add_product_inventory,
             commands::movement_cmd::add_material_inventory,
             commands::movement_cmd::list_recent_movements,
+            commands::movement_cmd::void_movement,
+            commands::production_cmd::list_production_batches,
+            commands::production_cmd::run_production_batch,
+            commands::production_cmd::list_curing_batches,
+            commands::production_cmd::release_cured_batch,
+            commands::purchase_cmd::list_purchase_orders,
+            commands::purchase_cmd::get_purchase_order,
+            commands::purchase_cmd::create_purchase_order,
+            commands::purchase_cmd::update_purchase_order,
+            commands::purchase_cmd::submit_purchase_order,
+            commands::purchase_cmd::cancel_purchase_order,
+            commands::purchase_cmd::receive_purchase_order,
+            commands::recipe_cmd::list_recipes,
+            commands::recipe_cmd::get_recipe,
+            commands::recipe_cmd::create_recipe,
+            commands::recipe_cmd::update_recipe,
+            commands::sales_cmd::list_sales_orders,
+            commands::sales_cmd::get_sales_order,
+            commands::sales_cmd::create_sales_order,
+            commands::sales_cmd::cancel_sales_order,
+            commands::sales_cmd::return_sales_items,
+            commands::sales_cmd::generate_invoice_pdf,
+            commands::settings_cmd::get_setting,
+            commands::settings_cmd::set_setting,
+            commands::stocktake_cmd::list_stocktakes,
+            commands::stocktake_cmd::get_stocktake,
+            commands::stocktake_cmd::create_stocktake,
+            commands::stocktake_cmd::record_stocktake_counts,
+            commands::stocktake_cmd::post_stocktake,
+            commands::stocktake_cmd::cancel_stocktake,
+            commands::stocktake_cmd::export_stocktake_sheet,
+            commands::stocktake_cmd::export_stocktake_variance,
+            commands::supplier_cmd::list_suppliers,
+            commands::supplier_cmd::add_supplier,
+            commands::supplier_cmd::update_supplier,
+            commands::supplier_cmd::remove_supplier,
+            commands::supplier_cmd::list_material_suppliers,
+            commands::supplier_cmd::set_material_supplier,
+            commands::supplier_cmd::unlink_material_supplier,
+            commands::trace_cmd::list_material_lots,
+            commands::trace_cmd::list_expiring_materials,
+            commands::trace_cmd::trace_material_lot,
+            commands::trace_cmd::trace_production_batch,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::models::movement::ItemType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarcodeFormat {
+    Ean13,
+    Code128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BarcodeImageType {
+    Svg,
+    Png,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Barcode {
+    pub format: BarcodeFormat,
+    /// The encoded text, including the EAN-13 check digit
+    pub value: String,
+    pub svg: String,
+}
+
+/// A product or material found by a scanned code
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CodeMatch {
+    pub item_type: ItemType,
+    pub item_id: i64,
+    pub name: String,
+    pub sku: Option<String>,
+    pub unit: String,
+    pub current_stock: f64,
+    /// The supplier whose item code matched, for materials found by a supplier code
+    pub supplier_name: Option<String>,
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MaterialValuation {
+    pub material_id: i64,
+    pub material_name: String,
+    pub category: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub avg_cost: Option<f64>,
+    /// quantity × avg_cost, 0 when the material has no cost yet
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoryValuation {
+    pub category: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InventoryValuation {
+    pub materials: Vec<MaterialValuation>,
+    pub categories: Vec<CategoryValuation>,
+    pub total_value: f64,
+    /// Materials in stock that have no average cost and are valued at 0
+    pub uncosted_count: i64,
+}
+
+/// Cost bucket a recipe material falls into
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostCategory {
+    Oils,
+    Lye,
+    Additives,
+    Fragrance,
+    Packaging,
+}
+
+/// Cost of one material used by a batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CostLine {
+    pub material_id: i64,
+    pub material_name: String,
+    pub category: CostCategory,
+    /// Quantity per batch, in the material's unit
+    pub quantity: f64,
+    pub unit: String,
+    pub avg_cost: Option<f64>,
+    pub cost: f64,
+}
+
+/// Cost of one finished unit of a product, rolled up from one of its recipes.
+/// Every amount except `batch_cost` is per unit.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductCost {
+    pub product_id: i64,
+    pub product_name: String,
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    pub yield_quantity: i64,
+    pub oils: f64,
+    pub lye: f64,
+    pub additives: f64,
+    pub fragrance: f64,
+    pub packaging: f64,
+    pub labour: f64,
+    pub total: f64,
+    pub batch_cost: f64,
+    pub lines: Vec<CostLine>,
+    /// Materials without an average cost, counted as 0
+    pub uncosted_materials: Vec<String>,
+    pub price: Option<f64>,
+    /// price − total
+    pub margin: Option<f64>,
+    /// margin as a percentage of price
+    pub margin_percent: Option<f64>,
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Which product price a customer pays by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum PriceTier {
+    #[default]
+    Retail,
+    Wholesale,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Customer {
+    pub id: i64,
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub price_tier: PriceTier,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateCustomerRequest {
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub price_tier: PriceTier,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateCustomerRequest {
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub price_tier: PriceTier,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RemoveCustomerRequest {
+    pub id: i64,
+}
+
+/// One product line a customer bought
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CustomerPurchase {
+    pub order_id: i64,
+    pub order_date: String,
+    pub product_id: i64,
+    pub product_name: String,
+    pub quantity: i64,
+    pub price: f64,
+    pub subtotal: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CustomerHistory {
+    pub customer: Customer,
+    pub order_count: i64,
+    pub total_spent: f64,
+    /// Newest orders first
+    pub purchases: Vec<CustomerPurchase>,
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum LyeType {
+    Naoh,
+    Koh,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OilWeight {
+    pub material_id: i64,
+    /// Weight in grams
+    pub weight: f64,
+}
+
+/// Either `recipe_id` or `oils` selects the oil blend. Water is given by exactly
+/// one of `water_ratio` (water : lye) or `lye_concentration` (percent).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CalculateLyeRequest {
+    pub recipe_id: Option<i64>,
+    pub oils: Option<Vec<OilWeight>>,
+    pub lye_type: LyeType,
+    pub superfat: f64,
+    pub water_ratio: Option<f64>,
+    pub lye_concentration: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OilLye {
+    pub material_id: i64,
+    pub material_name: String,
+    pub weight: f64,
+    pub sap: f64,
+    /// Lye needed for full saponification of this oil, before superfat
+    pub lye_weight: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LyeResult {
+    pub lye_type: LyeType,
+    pub superfat: f64,
+    pub oil_weight: f64,
+    pub lye_weight: f64,
+    pub water_weight: f64,
+    pub water_ratio: f64,
+    pub lye_concentration: f64,
+    pub oils: Vec<OilLye>,
+}
+
+/// Fatty-acid breakdown of an oil, in percent of total fatty acids
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow)]
+pub struct FattyAcidProfile {
+    pub lauric: f64,
+    pub myristic: f64,
+    pub palmitic: f64,
+    pub stearic: f64,
+    pub ricinoleic: f64,
+    pub oleic: f64,
+    pub linoleic: f64,
+    pub linolenic: f64,
+}
+
+/// Either `recipe_id` or `oils` selects the oil blend
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SoapQualityRequest {
+    pub recipe_id: Option<i64>,
+    pub oils: Option<Vec<OilWeight>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QualityScore {
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+    pub in_range: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SoapQuality {
+    /// Weighted fatty-acid composition of the whole oil blend
+    pub fatty_acids: FattyAcidProfile,
+    pub hardness: QualityScore,
+    pub cleansing: QualityScore,
+    pub conditioning: QualityScore,
+    pub bubbly: QualityScore,
+    pub creamy: QualityScore,
+    pub iodine: QualityScore,
+    pub ins: QualityScore,
+}
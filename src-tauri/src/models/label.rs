@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// One entry of a label's ingredient list. Materials sharing an INCI name are merged.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LabelIngredient {
+    pub name: String,
+    /// Total weight used by the batch, in grams
+    pub weight: f64,
+}
+
+/// Everything printed on the cosmetic label of a production batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchLabel {
+    pub batch_id: i64,
+    pub product_id: i64,
+    pub product_name: String,
+    pub lot_code: String,
+    /// YYYY-MM-DD
+    pub manufactured_on: String,
+    /// YYYY-MM-DD
+    pub expires_on: String,
+    pub manufacturer: Option<String>,
+    pub manufacturer_address: Option<String>,
+    pub manufacturer_phone: Option<String>,
+    /// Heaviest first
+    pub ingredients: Vec<LabelIngredient>,
+    /// Materials listed under their plain name because an INCI name is missing
+    pub missing_inci: Vec<String>,
+}
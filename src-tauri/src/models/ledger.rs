@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::movement::ItemType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerIssueKind {
+    /// A log row's `old_stock` differs from the previous row's `new_stock`,
+    /// i.e. stock changed without a log in between
+    Gap,
+    /// A log row's `new_stock` is not its `old_stock` moved by `change_amount`
+    /// in the direction of its `action_type`
+    RowMismatch,
+    /// The stored `current_stock` differs from the last logged `new_stock`
+    BalanceMismatch,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LedgerIssue {
+    pub kind: LedgerIssueKind,
+    pub item_type: ItemType,
+    pub item_id: i64,
+    pub item_name: String,
+    /// Log row the issue was found on; empty for balance mismatches
+    pub inventory_log_id: Option<i64>,
+    /// Stock according to the ledger
+    pub expected: f64,
+    /// Stock found on the log row, or the stored balance
+    pub actual: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LedgerReport {
+    pub items_checked: i64,
+    pub logs_checked: i64,
+    pub issues: Vec<LedgerIssue>,
+    /// Corrective `adj` entries posted in repair mode
+    pub corrections: Vec<i64>,
+}
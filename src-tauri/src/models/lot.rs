@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::models::production::ProductionBatch;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MaterialLot {
+    pub id: i64,
+    pub material_id: i64,
+    pub material_name: String,
+    pub material_unit: String,
+    pub lot_code: Option<String>,
+    pub received_at: String,
+    pub expires_at: Option<String>,
+    pub quantity: f64,
+    pub remaining_quantity: f64,
+    pub inventory_log_id: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+/// A production batch that consumed a material lot
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LotBatchUsage {
+    pub batch_id: i64,
+    pub batch_lot_code: Option<String>,
+    pub product_id: i64,
+    pub product_name: String,
+    pub produced_at: String,
+    pub quantity_used: f64,
+}
+
+/// Units of a production batch that left stock through a product movement
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct BatchSale {
+    pub batch_id: i64,
+    pub batch_lot_code: Option<String>,
+    pub inventory_log_id: i64,
+    pub product_name: String,
+    pub action_type: String,
+    pub quantity: i64,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+/// Material used by a production batch; `material_lot_id` is empty for stock
+/// that predates lot tracking
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct BatchMaterialUsage {
+    pub material_id: i64,
+    pub material_name: String,
+    pub material_unit: String,
+    pub material_lot_id: Option<i64>,
+    pub lot_code: Option<String>,
+    pub received_at: Option<String>,
+    pub quantity: f64,
+}
+
+/// Forward trace: material lot → production batches → sales
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MaterialLotTrace {
+    pub lot: MaterialLot,
+    pub batches: Vec<LotBatchUsage>,
+    pub sales: Vec<BatchSale>,
+}
+
+/// Backward trace: production batch → material lots, plus where its units went
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchTrace {
+    pub batch: ProductionBatch,
+    pub materials: Vec<BatchMaterialUsage>,
+    pub sales: Vec<BatchSale>,
+}
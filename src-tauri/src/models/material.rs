@@ -6,9 +6,19 @@ pub struct Material {
     pub id: i64,
     pub name: String,
     pub category: String,
+    pub sku: Option<String>,
     pub unit: String,
     pub current_stock: f64,
     pub low_stock_alert: Option<f64>,
+    pub sap_naoh: Option<f64>,
+    pub sap_koh: Option<f64>,
+    /// Grams per ml, used to weigh materials stocked by volume
+    pub density: Option<f64>,
+    /// Moving weighted-average cost per unit
+    pub avg_cost: Option<f64>,
+    pub inci_name: Option<String>,
+    /// INCI name of the oil's sodium salt, listed on labels of saponified soap
+    pub saponified_inci_name: Option<String>,
     pub note: Option<String>,
     pub created_at: String,
     pub deleted_at: Option<String>,
@@ -18,9 +28,18 @@ pub struct Material {
 pub struct CreateMaterialRequest {
     pub name: String,
     pub category: Option<String>,
+    /// Generated from the SKU pattern when left blank
+    #[serde(default)]
+    pub sku: Option<String>,
     pub unit: String,
     pub current_stock: f64,
     pub low_stock_alert: Option<f64>,
+    pub sap_naoh: Option<f64>,
+    pub sap_koh: Option<f64>,
+    #[serde(default)]
+    pub density: Option<f64>,
+    pub inci_name: Option<String>,
+    pub saponified_inci_name: Option<String>,
     pub note: Option<String>,
 }
 
@@ -28,8 +47,17 @@ pub struct CreateMaterialRequest {
 pub struct UpdateMaterialRequest {
     pub name: String,
     pub category: Option<String>,
+    /// Left unchanged when blank
+    #[serde(default)]
+    pub sku: Option<String>,
     pub unit: String,
     pub low_stock_alert: Option<f64>,
+    pub sap_naoh: Option<f64>,
+    pub sap_koh: Option<f64>,
+    #[serde(default)]
+    pub density: Option<f64>,
+    pub inci_name: Option<String>,
+    pub saponified_inci_name: Option<String>,
     pub note: Option<String>,
 }
 
@@ -1,3 +1,16 @@
+pub mod barcode;
+pub mod cost;
+pub mod customer;
+pub mod formula;
+pub mod label;
+pub mod ledger;
+pub mod lot;
 pub mod material;
 pub mod movement;
 pub mod product;
+pub mod production;
+pub mod purchase;
+pub mod recipe;
+pub mod sales;
+pub mod stocktake;
+pub mod supplier;
@@ -1,26 +1,179 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Kind of stock item an inventory log row belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum ItemType {
+    Material,
+    Product,
+}
+
+impl ItemType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ItemType::Material => "material",
+            ItemType::Product => "product",
+        }
+    }
+
+    /// Table holding the item and its `current_stock`
+    pub fn table(self) -> &'static str {
+        match self {
+            ItemType::Material => "materials",
+            ItemType::Product => "products",
+        }
+    }
+}
+
+/// What an inventory log row did to stock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum ActionType {
+    In,
+    Out,
+    /// Sets stock to a counted level
+    Adj,
+    Sale,
+    /// Bars a production batch added
+    Production,
+    /// Stock written off as damaged or spoiled
+    Waste,
+    /// Bars a customer sent back
+    Return,
+    /// Returned bars set aside from sellable stock
+    Quarantine,
+}
+
+impl ActionType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ActionType::In => "in",
+            ActionType::Out => "out",
+            ActionType::Adj => "adj",
+            ActionType::Sale => "sale",
+            ActionType::Production => "production",
+            ActionType::Waste => "waste",
+            ActionType::Return => "return",
+            ActionType::Quarantine => "quarantine",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Movement {
     pub id: i64,
     pub item_id: i64,
     pub item_name: String,
-    pub item_type: String,
+    pub item_type: ItemType,
     pub item_unit: String,
+    /// Quantity moved, positive with the direction given by `action_type`;
+    /// for `adj` entries the signed difference from the old stock
     pub change_amount: f64,
     pub old_stock: f64,
     pub new_stock: f64,
-    pub action_type: String,
+    pub action_type: ActionType,
+    /// Stock level an `adj` entry was asked to set
+    pub target_stock: Option<f64>,
     pub note: Option<String>,
+    pub batch_id: Option<i64>,
+    /// Lot code of the linked production batch
+    pub related_batch: Option<String>,
+    /// Cost per unit of stock received
+    pub unit_cost: Option<f64>,
+    pub purchase_order_id: Option<i64>,
+    pub sales_order_id: Option<i64>,
+    /// Movement this entry reverses
+    pub reversal_of: Option<i64>,
+    /// Stocktake session this adjustment was posted from
+    pub stocktake_session_id: Option<i64>,
+    /// Set once the entry has been voided
+    pub voided_at: Option<String>,
+    /// Reversing entry that voided this one
+    pub voided_by: Option<i64>,
+    /// Entry this one voids; unlike `reversal_of`, not set for returns
+    pub void_of: Option<i64>,
     pub created_at: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateMovementRequest {
-    pub item_type: String,
+    pub item_type: ItemType,
     pub item_id: i64,
-    pub action_type: String,
+    pub action_type: ActionType,
+    /// Quantity moved; for `adj`, the stock level to set
     pub change_amount: f64,
     pub note: Option<String>,
+    /// Supplier lot code, recorded when material stock is received
+    pub lot_code: Option<String>,
+    /// Date the material lot was received; defaults to now
+    pub received_at: Option<String>,
+    /// Expiry date (YYYY-MM-DD) of the material lot being received
+    pub expires_at: Option<String>,
+    /// Cost per unit of stock received
+    pub unit_cost: Option<f64>,
+}
+
+impl CreateMovementRequest {
+    pub fn new(
+        item_type: ItemType,
+        item_id: i64,
+        action_type: ActionType,
+        change_amount: f64,
+    ) -> Self {
+        CreateMovementRequest {
+            item_type,
+            item_id,
+            action_type,
+            change_amount,
+            note: None,
+            lot_code: None,
+            received_at: None,
+            expires_at: None,
+            unit_cost: None,
+        }
+    }
+}
+
+/// Records that caused an inventory log row, stored alongside it
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MovementLink {
+    pub batch_id: Option<i64>,
+    pub purchase_order_id: Option<i64>,
+    pub sales_order_id: Option<i64>,
+    pub reversal_of: Option<i64>,
+    pub stocktake_session_id: Option<i64>,
+}
+
+/// What happens when a movement would take an item's stock below zero
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NegativeStockPolicy {
+    Reject,
+    /// Let the movement through and report the shortage with it
+    Warn,
+    Allow,
+}
+
+/// An item a movement would take below zero
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockShortage {
+    pub item_type: ItemType,
+    pub item_id: i64,
+    pub item_name: String,
+    pub unit: String,
+    pub current_stock: f64,
+    /// Stock the movement takes out
+    pub requested: f64,
+    /// How far below zero the stock would end up
+    pub shortfall: f64,
+}
+
+/// A recorded movement, with the shortage it caused under the `warn` policy
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MovementResult {
+    pub movement: Movement,
+    pub warning: Option<StockShortage>,
 }
@@ -6,8 +6,17 @@ pub struct Product {
     pub id: i64,
     pub name: String,
     pub category: String,
+    pub sku: Option<String>,
     pub unit: String,
     pub current_stock: i64,
+    /// Units from production batches that have not been released yet
+    pub curing_stock: i64,
+    /// Units that can be sold now (`current_stock` minus `curing_stock`)
+    pub available_stock: i64,
+    /// Damaged returns held back from sale, not part of `current_stock`
+    pub quarantine_stock: i64,
+    pub retail_price: Option<f64>,
+    pub wholesale_price: Option<f64>,
     pub note: Option<String>,
     pub created_at: String,
     pub deleted_at: Option<String>,
@@ -17,8 +26,13 @@ pub struct Product {
 pub struct CreateProductRequest {
     pub name: String,
     pub category: String,
+    /// Generated from the SKU pattern when left blank
+    #[serde(default)]
+    pub sku: Option<String>,
     pub unit: String,
     pub current_stock: i64,
+    pub retail_price: Option<f64>,
+    pub wholesale_price: Option<f64>,
     pub note: Option<String>,
 }
 
@@ -26,6 +40,11 @@ pub struct CreateProductRequest {
 pub struct UpdateProductRequest {
     pub name: String,
     pub category: String,
+    /// Left unchanged when blank
+    #[serde(default)]
+    pub sku: Option<String>,
+    pub retail_price: Option<f64>,
+    pub wholesale_price: Option<f64>,
     pub note: Option<String>,
 }
 
@@ -33,3 +52,13 @@ pub struct UpdateProductRequest {
 pub struct RemoveProductRequest {
     pub id: i64,
 }
+
+/// A dated price change of a product
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ProductPrice {
+    pub id: i64,
+    pub product_id: i64,
+    pub retail_price: Option<f64>,
+    pub wholesale_price: Option<f64>,
+    pub effective_at: String,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::models::movement::StockShortage;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ProductionBatch {
+    pub id: i64,
+    pub product_id: i64,
+    pub product_name: String,
+    pub recipe_id: Option<i64>,
+    pub recipe_name: Option<String>,
+    pub batch_count: i64,
+    pub quantity: i64,
+    pub produced_at: String,
+    pub cure_started_at: Option<String>,
+    pub cure_ready_at: Option<String>,
+    /// Set once the batch has cured and its units became sellable
+    pub released_at: Option<String>,
+    pub lot_code: Option<String>,
+    pub note: Option<String>,
+    /// Materials the run took below zero under the `warn` policy
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub stock_warnings: Vec<StockShortage>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RunProductionBatchRequest {
+    pub product_id: i64,
+    pub recipe_id: i64,
+    pub batch_count: i64,
+    /// Finished units actually produced; defaults to the recipe yield times `batch_count`
+    pub quantity: Option<i64>,
+    /// Days the bars cure before they can be sold; defaults to four weeks, 0 skips curing
+    pub cure_days: Option<i64>,
+    pub note: Option<String>,
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Ordered,
+    PartiallyReceived,
+    Received,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PurchaseOrder {
+    pub id: i64,
+    pub supplier_id: i64,
+    pub supplier_name: String,
+    pub status: PurchaseOrderStatus,
+    pub ordered_at: Option<String>,
+    pub expected_at: Option<String>,
+    pub received_at: Option<String>,
+    pub cancelled_at: Option<String>,
+    /// Sum of quantity × unit cost over lines with a cost
+    pub total_cost: f64,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PurchaseOrderLine {
+    pub id: i64,
+    pub purchase_order_id: i64,
+    pub material_id: i64,
+    pub material_name: String,
+    pub material_unit: String,
+    pub quantity: f64,
+    pub received_quantity: f64,
+    /// Cost per material unit
+    pub unit_cost: Option<f64>,
+}
+
+/// A purchase order together with its lines.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderDetail {
+    #[serde(flatten)]
+    pub order: PurchaseOrder,
+    pub lines: Vec<PurchaseOrderLine>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PurchaseOrderLineRequest {
+    pub material_id: i64,
+    pub quantity: f64,
+    pub unit_cost: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePurchaseOrderRequest {
+    pub supplier_id: i64,
+    pub expected_at: Option<String>,
+    pub note: Option<String>,
+    pub lines: Vec<PurchaseOrderLineRequest>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdatePurchaseOrderRequest {
+    pub supplier_id: i64,
+    pub expected_at: Option<String>,
+    pub note: Option<String>,
+    pub lines: Vec<PurchaseOrderLineRequest>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReceiveLineRequest {
+    pub line_id: i64,
+    pub quantity: f64,
+    /// Invoiced cost per material unit; defaults to the ordered unit cost
+    pub unit_cost: Option<f64>,
+    /// Supplier lot code of the delivered material
+    pub lot_code: Option<String>,
+    /// Expiry date (YYYY-MM-DD) of the delivered material
+    pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReceivePurchaseOrderRequest {
+    pub purchase_order_id: i64,
+    /// Date the goods arrived; defaults to now
+    pub received_at: Option<String>,
+    pub note: Option<String>,
+    pub lines: Vec<ReceiveLineRequest>,
+}
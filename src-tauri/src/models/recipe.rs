@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::models::formula::LyeType;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Recipe {
+    pub id: i64,
+    pub product_id: i64,
+    pub product_name: String,
+    pub name: String,
+    pub yield_quantity: i64,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub lye: RecipeLye,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+/// Lye settings of a recipe. When `lye_material_id` is set, production deducts
+/// the calculated lye from that material instead of a fixed recipe line.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow)]
+pub struct RecipeLye {
+    pub lye_material_id: Option<i64>,
+    pub lye_type: Option<LyeType>,
+    pub superfat: Option<f64>,
+    pub water_ratio: Option<f64>,
+    pub lye_concentration: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RecipeLine {
+    pub id: i64,
+    pub recipe_id: i64,
+    pub material_id: i64,
+    pub material_name: String,
+    pub material_category: String,
+    pub material_unit: String,
+    /// Grams per ml of a material stocked by volume
+    pub material_density: Option<f64>,
+    pub quantity: f64,
+}
+
+/// A recipe together with the materials it uses.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecipeDetail {
+    #[serde(flatten)]
+    pub recipe: Recipe,
+    pub lines: Vec<RecipeLine>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RecipeLineRequest {
+    pub material_id: i64,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateRecipeRequest {
+    pub product_id: i64,
+    pub name: String,
+    pub yield_quantity: i64,
+    #[serde(flatten)]
+    pub lye: RecipeLye,
+    pub note: Option<String>,
+    pub lines: Vec<RecipeLineRequest>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateRecipeRequest {
+    pub name: String,
+    pub yield_quantity: i64,
+    #[serde(flatten)]
+    pub lye: RecipeLye,
+    pub note: Option<String>,
+    pub lines: Vec<RecipeLineRequest>,
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::models::movement::StockShortage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum SalesOrderStatus {
+    Completed,
+    PartiallyReturned,
+    Returned,
+    Cancelled,
+}
+
+/// Where returned bars go
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum ReturnCondition {
+    /// Back to sellable stock
+    Sellable,
+    /// Into the product's quarantine stock
+    Damaged,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SalesOrder {
+    pub id: i64,
+    pub customer_id: Option<i64>,
+    pub customer_name: Option<String>,
+    pub order_date: String,
+    pub total_amount: f64,
+    pub status: SalesOrderStatus,
+    pub refund_amount: f64,
+    pub cancelled_at: Option<String>,
+    /// Assigned when the first receipt is printed
+    pub invoice_number: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SalesItem {
+    pub id: i64,
+    pub order_id: i64,
+    pub product_id: i64,
+    pub product_name: String,
+    pub quantity: i64,
+    pub returned_quantity: i64,
+    pub price: f64,
+    pub subtotal: f64,
+    /// Inventory log row that deducted the stock
+    pub inventory_log_id: Option<i64>,
+}
+
+/// A sales order together with its items.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SalesOrderDetail {
+    #[serde(flatten)]
+    pub order: SalesOrder,
+    pub items: Vec<SalesItem>,
+    pub returns: Vec<SalesReturn>,
+    /// Bars sold beyond the stock on hand under the `warn` policy
+    #[serde(default)]
+    pub stock_warnings: Vec<StockShortage>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SalesReturn {
+    pub id: i64,
+    pub order_id: i64,
+    pub sales_item_id: i64,
+    pub product_id: i64,
+    pub product_name: String,
+    pub quantity: i64,
+    pub condition: ReturnCondition,
+    pub refund_amount: f64,
+    pub inventory_log_id: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SalesItemRequest {
+    pub product_id: i64,
+    pub quantity: i64,
+    /// Unit price; defaults to the product's price for the customer's price tier
+    pub price: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateSalesOrderRequest {
+    /// Walk-in sales have no customer
+    pub customer_id: Option<i64>,
+    /// Defaults to now
+    pub order_date: Option<String>,
+    pub note: Option<String>,
+    pub items: Vec<SalesItemRequest>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReturnItemRequest {
+    pub sales_item_id: i64,
+    pub quantity: i64,
+    pub condition: ReturnCondition,
+    /// Defaults to quantity × the item's unit price
+    pub refund_amount: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReturnSalesItemsRequest {
+    pub order_id: i64,
+    pub note: Option<String>,
+    pub items: Vec<ReturnItemRequest>,
+}
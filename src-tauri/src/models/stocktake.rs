@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::models::movement::ItemType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum StocktakeStatus {
+    /// Counts are still being entered
+    Counting,
+    /// Variances have been posted as adjustments
+    Posted,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct StocktakeSession {
+    pub id: i64,
+    pub name: String,
+    /// Both materials and products when empty
+    pub item_type: Option<ItemType>,
+    /// Category the count is limited to; every category when empty
+    pub category: Option<String>,
+    pub status: StocktakeStatus,
+    pub note: Option<String>,
+    pub started_at: String,
+    pub posted_at: Option<String>,
+    pub cancelled_at: Option<String>,
+    pub line_count: i64,
+    pub counted_count: i64,
+}
+
+/// An item to be counted. `system_stock` is the live stock until the line is
+/// counted, then the stock on record when it was counted.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct StocktakeLine {
+    pub id: i64,
+    pub session_id: i64,
+    pub item_type: ItemType,
+    pub item_id: i64,
+    pub item_name: String,
+    pub sku: Option<String>,
+    pub category: Option<String>,
+    pub unit: String,
+    pub system_stock: f64,
+    pub counted_quantity: Option<f64>,
+    /// Counted minus system stock, the change posted with the session
+    pub variance: Option<f64>,
+    /// Average cost of a material, used to value its variance
+    pub unit_cost: Option<f64>,
+    pub counted_at: Option<String>,
+    /// Adjustment written when the session was posted
+    pub inventory_log_id: Option<i64>,
+    pub note: Option<String>,
+}
+
+/// A stocktake session together with its lines.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StocktakeDetail {
+    #[serde(flatten)]
+    pub session: StocktakeSession,
+    pub lines: Vec<StocktakeLine>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateStocktakeRequest {
+    pub name: String,
+    pub item_type: Option<ItemType>,
+    pub category: Option<String>,
+    pub note: Option<String>,
+}
+
+/// A counted quantity for one line; an empty quantity clears the count
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StocktakeCountRequest {
+    pub line_id: i64,
+    pub counted_quantity: Option<f64>,
+    pub note: Option<String>,
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Supplier {
+    pub id: i64,
+    pub name: String,
+    pub contact: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateSupplierRequest {
+    pub name: String,
+    pub contact: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateSupplierRequest {
+    pub name: String,
+    pub contact: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RemoveSupplierRequest {
+    pub id: i64,
+}
+
+/// A supplier that sells a material, with their ordering details
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MaterialSupplier {
+    pub material_id: i64,
+    pub material_name: String,
+    pub material_unit: String,
+    pub supplier_id: i64,
+    pub supplier_name: String,
+    pub item_code: Option<String>,
+    /// Pack size in the material's unit
+    pub pack_size: Option<f64>,
+    /// Last price paid per pack
+    pub last_price: Option<f64>,
+    pub lead_time_days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MaterialSupplierRequest {
+    pub material_id: i64,
+    pub supplier_id: i64,
+    pub item_code: Option<String>,
+    pub pack_size: Option<f64>,
+    pub last_price: Option<f64>,
+    pub lead_time_days: Option<i64>,
+}
@@ -0,0 +1,133 @@
+use crate::models::movement::ItemType;
+use crate::models::stocktake::StocktakeDetail;
+use crate::pdf::{wrap_text, Document, A4};
+
+const MARGIN: f32 = 40.0;
+const ROW_HEIGHT: f32 = 22.0;
+const HEADINGS: [&str; 7] = ["#", "品名", "SKU", "分類", "單位", "實盤數量", "備註"];
+
+/// Renders a count sheet listing every line of a stocktake session. System
+/// stock is left off so the counters write down what is actually on the shelf.
+pub(crate) fn render(detail: &StocktakeDetail) -> Vec<u8> {
+    let session = &detail.session;
+    let mut doc = Document::new(&format!("盤點表 #{}", session.id), A4);
+    let right = doc.width() - MARGIN;
+    // Left edges of each column, then the right edge of the table
+    let columns = [
+        MARGIN,
+        MARGIN + 28.0,
+        MARGIN + 178.0,
+        MARGIN + 258.0,
+        MARGIN + 318.0,
+        MARGIN + 354.0,
+        MARGIN + 424.0,
+        right,
+    ];
+
+    let mut y = doc.height() - MARGIN - 10.0;
+    doc.text(MARGIN, y, 18.0, "盤 點 表");
+    doc.text_right(right, y, 10.0, &format!("盤點單：#{}", session.id));
+
+    let scope = match session.item_type {
+        Some(ItemType::Material) => "原料",
+        Some(ItemType::Product) => "成品",
+        None => "原料及成品",
+    };
+    let scope = match &session.category {
+        Some(category) => format!("{}（{}）", scope, category),
+        None => scope.to_string(),
+    };
+    let header_lines = [
+        format!("名稱：{}", session.name),
+        format!("範圍：{}", scope),
+        format!(
+            "開始日期：{}",
+            session.started_at.get(..10).unwrap_or(&session.started_at)
+        ),
+    ];
+    y -= 8.0;
+    for line in &header_lines {
+        y -= 14.0;
+        doc.text(MARGIN, y, 10.0, line);
+    }
+
+    let mut page = 1;
+    y = draw_table_header(&mut doc, &columns, y - 14.0);
+
+    for (index, line) in detail.lines.iter().enumerate() {
+        if y - ROW_HEIGHT < MARGIN + 20.0 {
+            draw_page_number(&mut doc, page);
+            doc.new_page();
+            page += 1;
+            let top = doc.height() - MARGIN;
+            y = draw_table_header(&mut doc, &columns, top);
+        }
+        y -= ROW_HEIGHT;
+
+        let name_width = columns[2] - columns[1] - 8.0;
+        let mut name = wrap_text(&line.item_name, 9.0, name_width)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        if name.len() < line.item_name.len() {
+            name.push('…');
+        }
+
+        doc.text(columns[0] + 4.0, y + 7.0, 9.0, &(index + 1).to_string());
+        doc.text(columns[1] + 4.0, y + 7.0, 9.0, &name);
+        doc.text(
+            columns[2] + 4.0,
+            y + 7.0,
+            9.0,
+            line.sku.as_deref().unwrap_or(""),
+        );
+        doc.text(
+            columns[3] + 4.0,
+            y + 7.0,
+            9.0,
+            line.category.as_deref().unwrap_or(""),
+        );
+        doc.text(columns[4] + 4.0, y + 7.0, 9.0, &line.unit);
+        doc.rect(
+            columns[5] + 4.0,
+            y + 3.0,
+            columns[6] - columns[5] - 8.0,
+            16.0,
+            0.5,
+        );
+        doc.line(columns[0], y, right, y, 0.3);
+    }
+
+    if y - 50.0 < MARGIN + 20.0 {
+        draw_page_number(&mut doc, page);
+        doc.new_page();
+        page += 1;
+        y = doc.height() - MARGIN;
+    }
+    y -= 40.0;
+    doc.text(
+        MARGIN,
+        y,
+        10.0,
+        "盤點人：＿＿＿＿＿＿    覆核人：＿＿＿＿＿＿    日期：＿＿＿＿＿＿",
+    );
+    draw_page_number(&mut doc, page);
+
+    doc.finish()
+}
+
+/// Draws the column headings below `y` and returns the y of their bottom rule
+fn draw_table_header(doc: &mut Document, columns: &[f32; 8], y: f32) -> f32 {
+    let bottom = y - ROW_HEIGHT;
+    doc.line(columns[0], y, columns[7], y, 1.0);
+    for (x, heading) in columns.iter().zip(HEADINGS) {
+        doc.text(x + 4.0, bottom + 7.0, 10.0, heading);
+    }
+    doc.line(columns[0], bottom, columns[7], bottom, 1.0);
+    bottom
+}
+
+fn draw_page_number(doc: &mut Document, page: usize) {
+    let center = doc.width() / 2.0;
+    doc.text_center(center, MARGIN - 20.0, 9.0, &format!("第 {} 頁", page));
+}
@@ -0,0 +1,175 @@
+use crate::models::customer::Customer;
+use crate::models::sales::SalesOrderDetail;
+use crate::pdf::{format_money, Document, A4};
+
+const MARGIN: f32 = 50.0;
+const ROW_HEIGHT: f32 = 20.0;
+
+/// Workshop details printed in the invoice header, from `app_settings`
+#[derive(Debug, Default)]
+pub(crate) struct WorkshopDetails {
+    pub name: Option<String>,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub tax_id: Option<String>,
+}
+
+/// Renders a sales order as a receipt. Returned quantities and refunds are
+/// listed so the totals match what the customer actually paid.
+pub(crate) fn render(
+    invoice_number: &str,
+    workshop: &WorkshopDetails,
+    customer: Option<&Customer>,
+    order: &SalesOrderDetail,
+) -> Vec<u8> {
+    let mut doc = Document::new(&format!("收據 {}", invoice_number), A4);
+    let right = doc.width() - MARGIN;
+
+    // Workshop header
+    let mut y = doc.height() - MARGIN - 10.0;
+    doc.text(
+        MARGIN,
+        y,
+        18.0,
+        workshop.name.as_deref().unwrap_or("手工皂工作室"),
+    );
+    doc.text_right(right, y, 20.0, "收 據");
+
+    let workshop_lines = [
+        workshop.address.clone(),
+        workshop
+            .phone
+            .as_ref()
+            .map(|phone| format!("電話：{}", phone)),
+        workshop
+            .email
+            .as_ref()
+            .map(|email| format!("Email：{}", email)),
+        workshop
+            .tax_id
+            .as_ref()
+            .map(|tax_id| format!("統一編號：{}", tax_id)),
+    ];
+    let document_lines = [
+        format!("單號：{}", invoice_number),
+        format!(
+            "日期：{}",
+            order
+                .order
+                .order_date
+                .get(..10)
+                .unwrap_or(&order.order.order_date)
+        ),
+        format!("銷售單：#{}", order.order.id),
+    ];
+
+    let mut left_y = y - 22.0;
+    for line in workshop_lines.iter().flatten() {
+        doc.text(MARGIN, left_y, 9.0, line);
+        left_y -= 13.0;
+    }
+    let mut right_y = y - 22.0;
+    for line in &document_lines {
+        doc.text_right(right, right_y, 10.0, line);
+        right_y -= 14.0;
+    }
+    y = left_y.min(right_y) - 6.0;
+    doc.line(MARGIN, y, right, y, 1.0);
+
+    // Customer
+    y -= 20.0;
+    match customer {
+        Some(customer) => {
+            doc.text(MARGIN, y, 11.0, &format!("客戶：{}", customer.name));
+            let contact: Vec<String> = [
+                customer
+                    .phone
+                    .as_ref()
+                    .map(|phone| format!("電話：{}", phone)),
+                customer
+                    .email
+                    .as_ref()
+                    .map(|email| format!("Email：{}", email)),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            if !contact.is_empty() {
+                y -= 15.0;
+                doc.text(MARGIN, y, 9.0, &contact.join("    "));
+            }
+        }
+        None => doc.text(MARGIN, y, 11.0, "客戶：門市散客"),
+    }
+
+    // Line items. Columns: left edge, then the right edges of quantity, unit price and subtotal
+    let columns = [MARGIN, right - 170.0, right - 90.0, right];
+    y -= 30.0;
+    y = draw_table_header(&mut doc, &columns, y);
+
+    for item in &order.items {
+        if y - ROW_HEIGHT < MARGIN + 40.0 {
+            doc.new_page();
+            let top = doc.height() - MARGIN;
+            y = draw_table_header(&mut doc, &columns, top);
+        }
+        y -= ROW_HEIGHT;
+
+        let mut name = item.product_name.clone();
+        if item.returned_quantity > 0 {
+            name = format!("{}（退貨 {}）", name, item.returned_quantity);
+        }
+        doc.text(columns[0] + 4.0, y + 6.0, 10.0, &name);
+        doc.text_right(columns[1] - 4.0, y + 6.0, 10.0, &item.quantity.to_string());
+        doc.text_right(columns[2] - 4.0, y + 6.0, 10.0, &format_money(item.price));
+        doc.text_right(
+            columns[3] - 4.0,
+            y + 6.0,
+            10.0,
+            &format_money(item.subtotal),
+        );
+        doc.line(MARGIN, y, right, y, 0.3);
+    }
+
+    // Totals
+    let mut totals = vec![("合計", order.order.total_amount)];
+    if order.order.refund_amount > 0.0 {
+        totals.push(("退款", -order.order.refund_amount));
+        totals.push((
+            "實收金額",
+            order.order.total_amount - order.order.refund_amount,
+        ));
+    }
+    if y - ROW_HEIGHT * (totals.len() as f32 + 2.0) < MARGIN {
+        doc.new_page();
+        y = doc.height() - MARGIN;
+    }
+    y -= 6.0;
+    for (label, amount) in totals {
+        y -= ROW_HEIGHT;
+        doc.text_right(columns[2] - 4.0, y + 6.0, 11.0, label);
+        doc.text_right(columns[3] - 4.0, y + 6.0, 11.0, &format_money(amount));
+    }
+
+    if let Some(note) = order.order.note.as_deref().filter(|note| !note.is_empty()) {
+        y -= ROW_HEIGHT * 1.5;
+        doc.text(MARGIN, y, 9.0, &format!("備註：{}", note));
+    }
+
+    doc.text_center(doc.width() / 2.0, MARGIN - 20.0, 9.0, "謝謝惠顧");
+
+    doc.finish()
+}
+
+/// Draws the column headings below `y` and returns the y of their bottom rule
+fn draw_table_header(doc: &mut Document, columns: &[f32; 4], y: f32) -> f32 {
+    let bottom = y - ROW_HEIGHT;
+    doc.line(columns[0], y, columns[3], y, 1.0);
+    doc.text(columns[0] + 4.0, bottom + 6.0, 10.0, "品名");
+    doc.text_right(columns[1] - 4.0, bottom + 6.0, 10.0, "數量");
+    doc.text_right(columns[2] - 4.0, bottom + 6.0, 10.0, "單價");
+    doc.text_right(columns[3] - 4.0, bottom + 6.0, 10.0, "小計");
+    doc.line(columns[0], bottom, columns[3], bottom, 1.0);
+    bottom
+}
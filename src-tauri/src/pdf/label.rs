@@ -0,0 +1,126 @@
+use crate::models::label::BatchLabel;
+use crate::pdf::svg::SvgPage;
+use crate::pdf::{wrap_text, Canvas, Document, A4};
+
+const MM: f32 = 72.0 / 25.4;
+const LABEL_WIDTH: f32 = 90.0 * MM;
+const LABEL_HEIGHT: f32 = 60.0 * MM;
+const COLUMNS: usize = 2;
+const ROWS: usize = 4;
+pub(crate) const LABELS_PER_PAGE: usize = COLUMNS * ROWS;
+
+const PADDING: f32 = 4.0 * MM;
+const TITLE_SIZE: f32 = 10.0;
+const DETAIL_SIZE: f32 = 7.0;
+/// The ingredient list shrinks down to this size before it is cut off
+const MIN_INGREDIENT_SIZE: f32 = 4.5;
+const LEADING: f32 = 1.3;
+
+/// Renders `copies` identical labels on A4 sheets of 90 × 60 mm labels,
+/// each framed with a thin cutting border
+pub(crate) fn render(label: &BatchLabel, copies: usize) -> Vec<u8> {
+    let mut doc = Document::new(&format!("{} {}", label.product_name, label.lot_code), A4);
+    for (copy, (x, y)) in slots(copies).enumerate() {
+        if copy > 0 && copy % LABELS_PER_PAGE == 0 {
+            doc.new_page();
+        }
+        draw_label(&mut doc, label, x, y);
+    }
+
+    doc.finish()
+}
+
+/// Renders the same sheets as `render`, one SVG per sheet, for saving as PNG
+pub(crate) fn render_svg(label: &BatchLabel, copies: usize) -> Vec<String> {
+    let mut sheets = Vec::new();
+    let mut page = SvgPage::new(A4);
+    for (copy, (x, y)) in slots(copies).enumerate() {
+        if copy > 0 && copy % LABELS_PER_PAGE == 0 {
+            sheets.push(std::mem::replace(&mut page, SvgPage::new(A4)).finish());
+        }
+        draw_label(&mut page, label, x, y);
+    }
+    sheets.push(page.finish());
+
+    sheets
+}
+
+/// Bottom-left corners of the labels, filling each sheet row by row
+fn slots(copies: usize) -> impl Iterator<Item = (f32, f32)> {
+    let (width, height) = A4;
+    let left = (width - LABEL_WIDTH * COLUMNS as f32) / 2.0;
+    let top = height - (height - LABEL_HEIGHT * ROWS as f32) / 2.0;
+
+    (0..copies).map(move |copy| {
+        let slot = copy % LABELS_PER_PAGE;
+        let x = left + (slot % COLUMNS) as f32 * LABEL_WIDTH;
+        let y = top - (slot / COLUMNS + 1) as f32 * LABEL_HEIGHT;
+        (x, y)
+    })
+}
+
+/// Draws one label with its bottom-left corner at (`x`, `y`)
+fn draw_label(doc: &mut impl Canvas, label: &BatchLabel, x: f32, y: f32) {
+    doc.rect(x, y, LABEL_WIDTH, LABEL_HEIGHT, 0.25);
+
+    let inner_left = x + PADDING;
+    let inner_width = LABEL_WIDTH - PADDING * 2.0;
+
+    let mut cursor = y + LABEL_HEIGHT - PADDING - TITLE_SIZE;
+    doc.text(
+        inner_left,
+        cursor,
+        TITLE_SIZE,
+        &format!("品名：{}", label.product_name),
+    );
+    cursor -= TITLE_SIZE * 0.6;
+
+    // Details are pinned to the bottom; the ingredients fill the space between
+    let mut details = vec![
+        format!(
+            "製造日期：{}    有效日期：{}",
+            label.manufactured_on, label.expires_on
+        ),
+        format!("批號：{}", label.lot_code),
+    ];
+    if let Some(manufacturer) = &label.manufacturer {
+        details.push(format!("製造商：{}", manufacturer));
+    }
+    if let Some(address) = &label.manufacturer_address {
+        details.push(format!("地址：{}", address));
+    }
+    if let Some(phone) = &label.manufacturer_phone {
+        details.push(format!("電話：{}", phone));
+    }
+    let details_top = y + PADDING + details.len() as f32 * DETAIL_SIZE * LEADING;
+
+    let ingredients = format!(
+        "全成分：{}",
+        label
+            .ingredients
+            .iter()
+            .map(|ingredient| ingredient.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let available = cursor - details_top - DETAIL_SIZE * 0.5;
+    let mut size = DETAIL_SIZE;
+    let mut lines = wrap_text(&ingredients, size, inner_width);
+    while lines.len() as f32 * size * LEADING > available && size > MIN_INGREDIENT_SIZE {
+        size -= 0.5;
+        lines = wrap_text(&ingredients, size, inner_width);
+    }
+    for line in lines {
+        cursor -= size * LEADING;
+        if cursor < details_top {
+            break;
+        }
+        doc.text(inner_left, cursor, size, &line);
+    }
+
+    let mut cursor = details_top;
+    for detail in details {
+        cursor -= DETAIL_SIZE * LEADING;
+        doc.text(inner_left, cursor, DETAIL_SIZE, &detail);
+    }
+}
@@ -0,0 +1,239 @@
+pub mod count_sheet;
+pub mod invoice;
+pub mod label;
+pub mod svg;
+
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+/// A4 portrait, in points
+pub(crate) const A4: (f32, f32) = (595.0, 842.0);
+
+const FONT_NAME: Name<'static> = Name(b"F1");
+/// Traditional Chinese font every PDF reader ships with, so nothing needs embedding
+const BASE_FONT: Name<'static> = Name(b"MSung-Light");
+
+/// A small page-by-page PDF builder writing text in a single CJK font.
+/// Coordinates are in points from the bottom-left corner of the page.
+pub(crate) struct Document {
+    width: f32,
+    height: f32,
+    title: String,
+    pages: Vec<Content>,
+}
+
+impl Document {
+    pub(crate) fn new(title: &str, (width, height): (f32, f32)) -> Self {
+        Self {
+            width,
+            height,
+            title: title.to_string(),
+            pages: vec![Content::new()],
+        }
+    }
+
+    pub(crate) fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub(crate) fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub(crate) fn new_page(&mut self) {
+        self.pages.push(Content::new());
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("document always has a page")
+    }
+
+    pub(crate) fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        let encoded = encode(text);
+        self.page()
+            .begin_text()
+            .set_font(FONT_NAME, size)
+            .next_line(x, y)
+            .show(Str(&encoded))
+            .end_text();
+    }
+
+    /// Writes text so that it ends at `x`
+    pub(crate) fn text_right(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        self.text(x - text_width(text, size), y, size, text);
+    }
+
+    pub(crate) fn text_center(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        self.text(x - text_width(text, size) / 2.0, y, size, text);
+    }
+
+    pub(crate) fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32) {
+        self.page()
+            .set_line_width(width)
+            .move_to(x1, y1)
+            .line_to(x2, y2)
+            .stroke();
+    }
+
+    pub(crate) fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, line_width: f32) {
+        self.page()
+            .set_line_width(line_width)
+            .rect(x, y, width, height)
+            .stroke();
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let info_id = Ref::new(3);
+        let font_id = Ref::new(4);
+        let cid_font_id = Ref::new(5);
+        let descriptor_id = Ref::new(6);
+        let first_page = 7;
+
+        let page_ids: Vec<Ref> = (0..self.pages.len() as i32)
+            .map(|i| Ref::new(first_page + i * 2))
+            .collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().copied())
+            .count(page_ids.len() as i32);
+        pdf.document_info(info_id)
+            .title(TextStr(&self.title))
+            .producer(TextStr("Soap Workshop"));
+
+        pdf.type0_font(font_id)
+            .base_font(BASE_FONT)
+            .encoding_predefined(Name(b"UniCNS-UCS2-H"))
+            .descendant_font(cid_font_id);
+
+        let mut cid_font = pdf.cid_font(cid_font_id);
+        cid_font
+            .subtype(CidFontType::Type0)
+            .base_font(BASE_FONT)
+            .system_info(SystemInfo {
+                registry: Str(b"Adobe"),
+                ordering: Str(b"CNS1"),
+                supplement: 5,
+            })
+            .font_descriptor(descriptor_id)
+            .default_width(1000.0);
+        // Half-width Latin glyphs
+        cid_font.widths().same(1, 98, 500.0);
+        cid_font.finish();
+
+        pdf.font_descriptor(descriptor_id)
+            .name(BASE_FONT)
+            .flags(FontFlags::SERIF | FontFlags::NON_SYMBOLIC)
+            .bbox(Rect::new(-160.0, -259.0, 1015.0, 888.0))
+            .italic_angle(0.0)
+            .ascent(880.0)
+            .descent(-120.0)
+            .cap_height(880.0)
+            .stem_v(93.0);
+
+        let media_box = Rect::new(0.0, 0.0, self.width, self.height);
+        for (page_id, content) in page_ids.iter().zip(self.pages) {
+            let content_id = Ref::new(page_id.get() + 1);
+
+            let mut page = pdf.page(*page_id);
+            page.parent(page_tree_id)
+                .media_box(media_box)
+                .contents(content_id);
+            page.resources().fonts().pair(FONT_NAME, font_id);
+            page.finish();
+
+            pdf.stream(content_id, &content.finish());
+        }
+
+        pdf.finish()
+    }
+}
+
+/// Drawing operations shared by the PDF and SVG renderers, in points from
+/// the bottom-left corner of the page
+pub(crate) trait Canvas {
+    fn text(&mut self, x: f32, y: f32, size: f32, text: &str);
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, line_width: f32);
+}
+
+impl Canvas for Document {
+    fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        Document::text(self, x, y, size, text)
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, line_width: f32) {
+        Document::rect(self, x, y, width, height, line_width)
+    }
+}
+
+/// Approximate width of `text` in points: half an em for ASCII, a full em otherwise
+pub(crate) fn text_width(text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| if c.is_ascii() { 0.5 } else { 1.0 })
+        .sum::<f32>()
+        * size
+}
+
+/// Breaks text into lines no wider than `max_width`, preferring to break after
+/// spaces and commas so INCI names stay whole where possible
+pub(crate) fn wrap_text(text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    // Byte index in `line` just after the last good break point
+    let mut last_break = None;
+
+    for c in text.chars() {
+        line.push(c);
+        if c == ' ' || c == ',' || c == '，' || c == '、' {
+            last_break = Some(line.len());
+        }
+
+        if text_width(line.trim_end(), size) > max_width && line.chars().count() > 1 {
+            let split_at = last_break
+                .filter(|&at| at < line.len())
+                .unwrap_or_else(|| line.len() - c.len_utf8());
+            let rest = line.split_off(split_at);
+            lines.push(line.trim_end().to_string());
+            line = rest.trim_start().to_string();
+            last_break = None;
+        }
+    }
+    if !line.trim().is_empty() {
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines
+}
+
+/// Encodes text as UCS-2 big endian for the `UniCNS-UCS2-H` CMap. Characters
+/// outside the Basic Multilingual Plane are replaced with a question mark.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| u16::try_from(c as u32).unwrap_or(b'?' as u16))
+        .flat_map(u16::to_be_bytes)
+        .collect()
+}
+
+/// Formats an amount with two decimals and thousands separators
+pub(crate) fn format_money(amount: f64) -> String {
+    let formatted = format!("{:.2}", amount.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if amount < 0.0 && formatted != "0.00" {
+        "-"
+    } else {
+        ""
+    };
+    format!("{}{}.{}", sign, grouped, fraction)
+}
@@ -0,0 +1,61 @@
+use crate::pdf::Canvas;
+
+/// Serif CJK fonts tried in order when the page is rasterised
+const FONT_FAMILY: &str = "'PMingLiU', 'MingLiU', 'Songti TC', 'Noto Serif CJK TC', serif";
+
+/// A single page drawn as SVG, for the webview to rasterise where a PDF won't
+/// do. Takes the same bottom-left coordinates as `Document`.
+pub(crate) struct SvgPage {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl SvgPage {
+    pub(crate) fn new((width, height): (f32, f32)) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    pub(crate) fn finish(self) -> String {
+        let (width, height) = (self.width, self.height);
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}pt" height="{height}pt" viewBox="0 0 {width} {height}"><rect width="{width}" height="{height}" fill="white"/>{}</svg>"#,
+            self.body
+        )
+    }
+}
+
+impl Canvas for SvgPage {
+    fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        self.body.push_str(&format!(
+            r#"<text x="{:.2}" y="{:.2}" font-family="{}" font-size="{}" xml:space="preserve">{}</text>"#,
+            x,
+            self.height - y,
+            FONT_FAMILY,
+            size,
+            escape_xml(text)
+        ));
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, line_width: f32) {
+        self.body.push_str(&format!(
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="none" stroke="black" stroke-width="{}"/>"#,
+            x,
+            self.height - y - height,
+            width,
+            height,
+            line_width
+        ));
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}